@@ -1,2 +1,3 @@
 #include <gphoto2/gphoto2.h>
 #include <gphoto2/gphoto2-version.h>
+#include <gphoto2/gphoto2-port-version.h>
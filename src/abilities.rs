@@ -2,15 +2,60 @@
 //!
 //! The device abilities describe the abilities of the driver used to connect to a device.
 
-use crate::helper::{as_ref, bitflags, char_slice_to_cow};
-use crate::task::BackgroundPtr;
+use crate::helper::{as_ref, bitflags, char_slice_to_cow, to_c_string, UninitBox};
+use crate::task::{BackgroundPtr, Task};
 use crate::{context::Context, try_gp_internal, Result};
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, ffi, fmt, ops::Range, os::raw::c_int};
 
-pub(crate) struct AbilitiesList {
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+
+/// List of all camera models supported by libgphoto2
+///
+/// Can be loaded with [`Context::abilities_list`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use gphoto2::{Context, Result};
+///
+/// # fn main() -> Result<()> {
+/// let context = Context::new()?;
+///
+/// for abilities in context.abilities_list().wait()?.iter() {
+///   println!("{} ({:?})", abilities.model(), abilities.driver_status());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AbilitiesList {
   pub(crate) inner: BackgroundPtr<libgphoto2_sys::CameraAbilitiesList>,
 }
 
+/// Iterator over the [`Abilities`] of an [`AbilitiesList`]
+pub struct AbilitiesIter<'a> {
+  list: &'a AbilitiesList,
+  range: Range<c_int>,
+}
+
+impl Iterator for AbilitiesIter<'_> {
+  type Item = Abilities;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.range.next().map(|i| self.list.get_unchecked(i))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.range.size_hint()
+  }
+}
+
+impl ExactSizeIterator for AbilitiesIter<'_> {
+  fn len(&self) -> usize {
+    self.range.len()
+  }
+}
+
 /// Provides functions to get device abilities
 ///
 /// ## Abilities
@@ -27,6 +72,7 @@ pub struct Abilities {
 
 /// Camera USB information
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UsbInfo {
   /// Vendor ID
   pub vendor: u16,
@@ -108,21 +154,136 @@ impl fmt::Debug for Abilities {
   }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Abilities {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("Abilities", 8)?;
+    s.serialize_field("id", &self.id())?;
+    s.serialize_field("model", &self.model())?;
+    s.serialize_field("driver_status", &self.driver_status())?;
+    s.serialize_field("camera_operations", &self.camera_operations())?;
+    s.serialize_field("file_operations", &self.file_operations())?;
+    s.serialize_field("folder_operations", &self.folder_operations())?;
+    s.serialize_field("device_type", &self.device_type())?;
+    s.serialize_field("usb_info", &self.usb_info())?;
+    s.end()
+  }
+}
+
 as_ref!(AbilitiesList -> libgphoto2_sys::CameraAbilitiesList, **self.inner);
 
 as_ref!(Abilities -> libgphoto2_sys::CameraAbilities, self.inner);
 
 impl AbilitiesList {
   /// Must be called from a [`Task`]
+  ///
+  /// Loads the drivers from the directory set with
+  /// [`ContextBuilder::camlibs_dir`](crate::context::ContextBuilder::camlibs_dir), if any.
   pub(crate) fn new_inner(context: &Context) -> Result<Self> {
     try_gp_internal!(gp_abilities_list_new(&out abilities_inner)?);
-    try_gp_internal!(gp_abilities_list_load(abilities_inner, *context.inner)?);
+    let abilities_list = Self { inner: BackgroundPtr(abilities_inner) };
+
+    match context.camlibs_dir() {
+      Some(dir) => {
+        try_gp_internal!(gp_abilities_list_load_dir(
+          abilities_inner,
+          to_c_string!(dir),
+          *context.inner
+        )?);
+      }
+      None => {
+        try_gp_internal!(gp_abilities_list_load(abilities_inner, *context.inner)?);
+      }
+    }
+
+    Ok(abilities_list)
+  }
+
+  fn count(&self) -> c_int {
+    try_gp_internal!(let count = gp_abilities_list_count(*self.inner).unwrap());
+    count
+  }
+
+  fn get_unchecked(&self, index: c_int) -> Abilities {
+    let mut inner = UninitBox::uninit();
+
+    try_gp_internal!(
+      gp_abilities_list_get_abilities(*self.inner, index, inner.as_mut_ptr()).unwrap()
+    );
+
+    Abilities { inner: unsafe { inner.assume_init() } }
+  }
+
+  /// Number of camera models in the list
+  pub fn len(&self) -> usize {
+    self.count().try_into().unwrap()
+  }
+
+  /// Check if the list is empty
+  pub fn is_empty(&self) -> bool {
+    self.count() == 0
+  }
+
+  /// Get the abilities at an index
+  pub fn get(&self, index: usize) -> Option<Abilities> {
+    let index = c_int::try_from(index).ok().filter(|index| *index < self.count())?;
+
+    Some(self.get_unchecked(index))
+  }
+
+  /// Find the abilities of a camera model by its name
+  ///
+  /// Returns [`ErrorKind::ModelNotFound`](crate::error::ErrorKind::ModelNotFound)
+  /// if the model is not supported by libgphoto2.
+  pub fn find_model(&self, model: &str) -> Result<Abilities> {
+    try_gp_internal!(let index = gp_abilities_list_lookup_model(*self.inner, to_c_string!(model))?);
 
-    Ok(Self { inner: BackgroundPtr(abilities_inner) })
+    Ok(self.get_unchecked(index))
+  }
+
+  /// Iterate over the abilities of all camera models
+  pub fn iter(&self) -> AbilitiesIter<'_> {
+    AbilitiesIter { list: self, range: 0..self.count() }
+  }
+}
+
+impl<'a> IntoIterator for &'a AbilitiesList {
+  type Item = Abilities;
+  type IntoIter = AbilitiesIter<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl fmt::Debug for AbilitiesList {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("AbilitiesList").field("len", &self.len()).finish()
   }
 }
 
 impl Abilities {
+  /// Look up the abilities of a camera model without a connected camera
+  ///
+  /// ```no_run
+  /// use gphoto2::{abilities::Abilities, Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  ///
+  /// let abilities = Abilities::for_model(&context, "Nikon DSC D750").wait()?;
+  /// println!("Can capture images: {}", abilities.camera_operations().capture_image());
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn for_model(context: &Context, model: &str) -> Task<Result<Abilities>> {
+    let task_context = context.clone();
+    let model = model.to_owned();
+
+    unsafe { Task::new(move || AbilitiesList::new_inner(&task_context)?.find_model(&model)) }
+      .context(context.inner)
+  }
+
   /// Camera ID
   pub fn id(&self) -> Cow<str> {
     char_slice_to_cow(&self.inner.id)
@@ -1,23 +1,41 @@
 //! Cameras and camera events
 
 use crate::{
-  abilities::Abilities,
+  abilities::{Abilities, CameraOperations},
+  config::{self, ConfigDiff, ConfigEntry, ConfigKeyError, ConfigSnapshot, ConfigTransaction},
   file::{CameraFile, CameraFilePath},
   filesys::{CameraFS, StorageInfo},
+  focus::{self, FocusStep},
   helper::{as_ref, char_slice_to_cow, chars_to_string, to_c_string, UninitBox},
-  port::PortInfo,
-  task::{BackgroundPtr, Task},
+  port::{PortInfo, PortInfoList},
+  preview::PreviewStream,
+  retry::RetryPolicy,
+  settings::{CaptureTarget, Settings},
+  task::{BackgroundPtr, CancellationToken, Task},
+  timelapse::Timelapse,
   try_gp_internal,
-  widget::{GroupWidget, Widget, WidgetBase},
+  widget::{GroupWidget, RadioWidget, TextWidget, ToggleWidget, Widget, WidgetBase},
+  worker,
+  zoom::{self, ZoomCommand},
   Context, Error, Result,
 };
-use std::{ffi, os::raw::c_char, time::Duration};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+  ffi,
+  os::raw::c_char,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+  thread::{self, JoinHandle},
+  time::{Duration, Instant, SystemTime},
+};
 
 /// Event from camera
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum CameraEvent {
   /// Unknown event
+  ///
+  /// Contains the description reported by the driver, e.g. a PTP property change.
   Unknown(String),
   /// Timeout, no event,
   Timeout,
@@ -34,6 +52,135 @@ pub enum CameraEvent {
   CaptureComplete,
 }
 
+/// Kind of a [`CameraEvent`] without its data, used to filter events
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CameraEventKind {
+  /// [`CameraEvent::Unknown`]
+  Unknown,
+  /// [`CameraEvent::Timeout`]
+  Timeout,
+  /// [`CameraEvent::NewFile`]
+  NewFile,
+  /// [`CameraEvent::FileChanged`]
+  FileChanged,
+  /// [`CameraEvent::NewFolder`]
+  NewFolder,
+  /// [`CameraEvent::CaptureComplete`]
+  CaptureComplete,
+}
+
+impl CameraEvent {
+  /// Kind of the event
+  pub fn kind(&self) -> CameraEventKind {
+    match self {
+      Self::Unknown(_) => CameraEventKind::Unknown,
+      Self::Timeout => CameraEventKind::Timeout,
+      Self::NewFile(_) => CameraEventKind::NewFile,
+      Self::FileChanged(_) => CameraEventKind::FileChanged,
+      Self::NewFolder(_) => CameraEventKind::NewFolder,
+      Self::CaptureComplete => CameraEventKind::CaptureComplete,
+    }
+  }
+}
+
+/// Iterator over events of a camera
+///
+/// Created by [`Camera::events`], every call to [`next`](Iterator::next) blocks until
+/// the camera reports an event or the timeout elapses. The iterator never ends.
+pub struct CameraEventIter {
+  camera: Camera,
+  timeout: Duration,
+  skip_timeouts: bool,
+}
+
+impl CameraEventIter {
+  /// Don't yield [`CameraEvent::Timeout`], keep waiting for the next event instead
+  pub fn skip_timeouts(mut self) -> Self {
+    self.skip_timeouts = true;
+    self
+  }
+}
+
+impl Iterator for CameraEventIter {
+  type Item = Result<CameraEvent>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      match self.camera.wait_event(self.timeout).wait() {
+        Ok(CameraEvent::Timeout) if self.skip_timeouts => continue,
+        result => return Some(result),
+      }
+    }
+  }
+}
+
+/// Handle of a background thread waiting for the events of a camera
+///
+/// Created by [`Camera::spawn_event_pump`] and [`Camera::on_event`]. The thread stops when the
+/// handle is dropped, or when the receiver of the events is dropped.
+pub struct EventPump {
+  stop: Option<Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl EventPump {
+  /// Stop the thread and wait until it finished
+  ///
+  /// Blocks for up to the poll timeout of the pump.
+  pub fn shutdown(mut self) {
+    self.stop.take();
+
+    if let Some(thread) = self.thread.take() {
+      thread.join().ok();
+    }
+  }
+
+  /// Check if the thread is still running
+  ///
+  /// The thread stops on its own if the camera keeps failing or the receiver was dropped.
+  pub fn is_running(&self) -> bool {
+    self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+  }
+}
+
+/// Type of a capture
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CaptureType {
+  /// Still image
+  Image,
+  /// Movie
+  Movie,
+  /// Audio recording
+  Sound,
+}
+
+impl From<libgphoto2_sys::CameraCaptureType> for CaptureType {
+  fn from(capture_type: libgphoto2_sys::CameraCaptureType) -> Self {
+    use libgphoto2_sys::CameraCaptureType as GPCaptureType;
+
+    match capture_type {
+      GPCaptureType::GP_CAPTURE_IMAGE => Self::Image,
+      GPCaptureType::GP_CAPTURE_MOVIE => Self::Movie,
+      GPCaptureType::GP_CAPTURE_SOUND => Self::Sound,
+    }
+  }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<libgphoto2_sys::CameraCaptureType> for CaptureType {
+  fn into(self) -> libgphoto2_sys::CameraCaptureType {
+    use libgphoto2_sys::CameraCaptureType as GPCaptureType;
+
+    match self {
+      Self::Image => GPCaptureType::GP_CAPTURE_IMAGE,
+      Self::Movie => GPCaptureType::GP_CAPTURE_MOVIE,
+      Self::Sound => GPCaptureType::GP_CAPTURE_SOUND,
+    }
+  }
+}
+
 /// Represents a camera
 ///
 /// Cameras can only be created from a [`Context`](crate::Context) by using either
@@ -81,24 +228,106 @@ pub enum CameraEvent {
 /// camera.set_config(&iso).wait()?;
 /// # Ok(())
 /// # }
+/// ```
+///
+/// ## Threads
+///
+/// [`Camera`] is [`Send`] and [`Sync`], and cloning it is cheap (it only increases
+/// the libgphoto2 reference count). All libgphoto2 calls which communicate with the camera
+/// run on a single background thread, so operations from different threads are serialized
+/// and never access the device at the same time.
+///
+/// A few accessors only read data libgphoto2 keeps in memory and call libgphoto2 on the
+/// calling thread: [`Camera::abilities`], [`Camera::port_info`], [`CameraFile::data`](crate::file::CameraFile::data)
+/// and the getters and setters of [`Widget`]s, which only change the local copy until it is
+/// applied with [`Camera::set_config`].
+///
+/// This example downloads files on a worker thread while the main thread waits for events
+///
+/// ```no_run
+/// use gphoto2::{camera::CameraEvent, Context, Result};
+/// use std::{path::Path, sync::mpsc, thread, time::Duration};
+///
+/// # fn main() -> Result<()> {
+/// let context = Context::new()?;
+/// let camera = context.autodetect_camera().wait()?;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let worker_camera = camera.clone();
+///
+/// let worker = thread::spawn(move || -> Result<()> {
+///   for file in receiver {
+///     let name = file.name();
+///     worker_camera.fs().download_to(&file.folder(), &name, Path::new(name.as_ref())).wait()?;
+///   }
+///   Ok(())
+/// });
+///
+/// for event in camera.events(Duration::from_secs(10)).take(100) {
+///   if let CameraEvent::NewFile(file) = event? {
+///     sender.send(file).unwrap();
+///   }
+/// }
+///
+/// drop(sender);
+/// worker.join().unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct Camera {
   pub(crate) camera: BackgroundPtr<libgphoto2_sys::Camera>,
   pub(crate) context: Context,
+  cache: Arc<Mutex<CameraCache>>,
+  retry_policy: RetryPolicy,
+  auto_reconnect: bool,
+  handles: Arc<()>,
+}
+
+/// Information read from the camera once, shared by all clones of a [`Camera`]
+#[derive(Default)]
+struct CameraCache {
+  operations: Option<CameraOperations>,
+  /// Reset by [`Camera::reconnect`], since the port changes
+  identity: Option<Arc<str>>,
+  serial_number: Option<String>,
 }
 
 impl Clone for Camera {
   fn clone(&self) -> Self {
     try_gp_internal!(gp_camera_ref(*self.camera).unwrap());
-    Self { camera: self.camera, context: self.context.clone() }
+    Self {
+      camera: self.camera,
+      context: self.context.clone(),
+      cache: self.cache.clone(),
+      retry_policy: self.retry_policy,
+      auto_reconnect: self.auto_reconnect,
+      handles: self.handles.clone(),
+    }
   }
 }
 
 impl Drop for Camera {
   fn drop(&mut self) {
     let camera = self.camera;
+    let context = self.context.inner;
+    let handles = std::mem::replace(&mut self.handles, Arc::new(()));
 
     unsafe {
       Task::new(move || -> Result<()> {
+        // Exit explicitly so the device is released with our context
+        // and errors while closing the session don't prevent the camera from being freed.
+        // Handles are dropped one after another on this thread, so only the last one gets the Arc.
+        if Arc::into_inner(handles).is_some() {
+          let exit = || -> Result<()> {
+            try_gp_internal!(gp_camera_exit(*camera, *context)?);
+            Ok(())
+          };
+
+          if let Err(error) = exit() {
+            log::warn!("Could not exit camera: {}", error);
+          }
+        }
+
         try_gp_internal!(gp_camera_unref(*camera)?);
 
         Ok(())
@@ -113,11 +342,56 @@ as_ref!(Camera -> libgphoto2_sys::Camera, **self.camera);
 
 impl Camera {
   pub(crate) fn new(camera: BackgroundPtr<libgphoto2_sys::Camera>, context: Context) -> Self {
-    Self { camera, context }
+    Self {
+      camera,
+      context,
+      cache: Arc::default(),
+      retry_policy: RetryPolicy::default(),
+      auto_reconnect: false,
+      handles: Arc::new(()),
+    }
   }
 
   /// Capture image
   pub fn capture_image(&self) -> Task<Result<CameraFilePath>> {
+    self.capture(CaptureType::Image)
+  }
+
+  /// Capture an image and download it to `dest` on the host
+  ///
+  /// Returns the path the image was saved to. `dest` can be a directory or a file name
+  /// template, see [`CameraFilePath::download_to`]. The image stays on the camera,
+  /// use [`CameraFilePath::take`] with [`Camera::capture_image`] to move it instead.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// let path = camera.capture_and_download("captures/").wait()?;
+  /// println!("Saved {}", path.display());
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn capture_and_download(&self, dest: impl AsRef<Path>) -> Task<Result<PathBuf>> {
+    let camera = self.clone();
+    let dest = dest.as_ref().to_owned();
+
+    Task::blocking(move |cancel| {
+      // gp_camera_capture only returns once the camera reported the captured file
+      let file = camera.capture_image().wait_with(cancel)?;
+      cancel.check()?;
+
+      file.download_to(&camera, dest)
+    })
+  }
+
+  /// Capture a file of the given [`CaptureType`]
+  ///
+  /// Check [`CameraOperations`](crate::abilities::CameraOperations) to see which
+  /// capture types are supported by the camera.
+  pub fn capture(&self, capture_type: CaptureType) -> Task<Result<CameraFilePath>> {
     let camera = self.camera;
     let context = self.context.inner;
 
@@ -127,7 +401,7 @@ impl Camera {
 
         try_gp_internal!(gp_camera_capture(
           *camera,
-          libgphoto2_sys::CameraCaptureType::GP_CAPTURE_IMAGE,
+          capture_type.into(),
           inner.as_mut_ptr(),
           *context
         )?);
@@ -136,185 +410,1211 @@ impl Camera {
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
   /// Trigger a capture, without waiting for an image to be returned.
   ///
-  /// The image can later be retreived by listening for the [`CameraEvent::NewFile`] event.
+  /// The image can later be retrieved by listening for the [`CameraEvent::NewFile`] event.
+  ///
+  /// ```no_run
+  /// use gphoto2::{camera::CameraEvent, Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let camera = context.autodetect_camera().wait()?;
+  ///
+  /// camera.trigger_capture().wait()?;
+  ///
+  /// loop {
+  ///   if let CameraEvent::NewFile(file) = camera.wait_event(Duration::from_secs(5)).wait()? {
+  ///     println!("Captured {}", file.name());
+  ///     break;
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
   pub fn trigger_capture(&self) -> Task<Result<()>> {
     let camera = self.camera;
     let context = self.context.inner;
 
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_camera_trigger_capture(*camera, *context)?);
+        try_gp_internal!(gp_camera_trigger_capture(*camera, *context)?);
+
+        Ok(())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Take a bulb exposure of the given duration
+  ///
+  /// Opens the shutter, waits for `duration`, closes the shutter and waits for the captured file.
+  /// This uses the `bulb` toggle (Nikon and others) or the `eosremoterelease` configuration (Canon),
+  /// the camera must already be set to bulb mode.
+  ///
+  /// The exposure runs on its own thread, so the libgphoto2 thread stays available for other
+  /// cameras. Closing the shutter is retried with the [retry policy](Camera::retry_policy) of
+  /// the camera, and also attempted if opening it failed.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// let file = camera.bulb_capture(Duration::from_secs(30)).wait()?;
+  /// println!("Captured {}", file.name());
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn bulb_capture(&self, duration: Duration) -> Task<Result<CameraFilePath>> {
+    let camera = self.clone();
+
+    Task::blocking(move |cancel| {
+      match camera.config_key::<ToggleWidget>("bulb").wait_with(cancel) {
+        Ok(bulb) => {
+          camera.hold_shutter(duration, cancel, &bulb, |pressed| bulb.try_set_toggled(pressed))?
+        }
+        Err(error) if error.is_missing_config() => {
+          let remote_release =
+            camera.config_key::<RadioWidget>("eosremoterelease").wait_with(cancel)?;
+
+          camera.hold_shutter(duration, cancel, &remote_release, |pressed| {
+            remote_release.set_choice(if pressed { "Press Full" } else { "Release Full" })
+          })?;
+        }
+        Err(error) => return Err(error),
+      }
+
+      // Long exposures may be followed by a noise reduction exposure of the same length
+      camera.wait_for_file(duration + Duration::from_secs(30)).wait_with(cancel)
+    })
+  }
+
+  /// Open the shutter with `widget`, wait for `duration` and close it again
+  ///
+  /// `set_pressed` updates the value of `widget` to open or close the shutter. The shutter is
+  /// closed early if `cancel` is cancelled during the exposure.
+  fn hold_shutter(
+    &self,
+    duration: Duration,
+    cancel: &CancellationToken,
+    widget: &WidgetBase,
+    set_pressed: impl Fn(bool) -> Result<()>,
+  ) -> Result<()> {
+    const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+    let opened = set_pressed(true).and_then(|()| self.set_config(widget).wait_with(cancel));
+
+    if opened.is_ok() {
+      let deadline = deadline_after(duration);
+
+      while !cancel.is_cancelled() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        if remaining.is_zero() {
+          break;
+        }
+
+        thread::sleep(remaining.min(CANCEL_CHECK_INTERVAL));
+      }
+    }
+
+    // Always try to close the shutter, the camera may have opened it even if setting the
+    // configuration failed
+    let closed = set_pressed(false).and_then(|()| {
+      self.retry_policy.run(|| self.set_config(widget).wait()).map_err(|error| {
+        log::error!("Could not close the shutter, it may still be open: {}", error);
+        error
+      })
+    });
+
+    opened.and(closed).and_then(|()| cancel.check())
+  }
+
+  /// Capture `count` images as fast as the camera allows and return their files in order
+  ///
+  /// Every image is taken with [`Camera::trigger_capture`], retrying with the
+  /// [retry policy](Camera::retry_policy) of the camera while it is busy. Files reported
+  /// between the triggers are collected without waiting, afterwards this waits up to
+  /// 30 seconds for every missing image.
+  ///
+  /// Returns exactly `count` entries, one per image, each containing the files of that image.
+  /// Cameras saving RAW and JPEG report two files with the same name but different extensions
+  /// per image, which are grouped into the same entry. A file of the last image which is
+  /// reported after this returned is not included, it can be received with
+  /// [`Camera::wait_for_file`].
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// for (index, files) in camera.capture_burst(5).wait()?.iter().enumerate() {
+  ///   for file in files {
+  ///     println!("Image {} saved as {}", index, file);
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn capture_burst(&self, count: usize) -> Task<Result<Vec<Vec<CameraFilePath>>>> {
+    let camera = self.clone();
+
+    Task::blocking(move |cancel| {
+      let mut images = Vec::with_capacity(count);
+
+      let drain_files = |images: &mut Vec<Vec<CameraFilePath>>| -> Result<()> {
+        loop {
+          match camera.wait_event(Duration::ZERO).wait_with(cancel)? {
+            CameraEvent::NewFile(file) => add_burst_file(images, count, file),
+            CameraEvent::Timeout => return Ok(()),
+            _ => {}
+          }
+        }
+      };
+
+      for _ in 0..count {
+        camera.retry_policy.run(|| camera.trigger_capture().wait_with(cancel))?;
+
+        // Many cameras stop accepting triggers while events are queued
+        drain_files(&mut images)?;
+      }
+
+      while images.len() < count {
+        let file = camera.wait_for_file(Duration::from_secs(30)).wait_with(cancel)?;
+        add_burst_file(&mut images, count, file);
+      }
+
+      // Pick up the other files of the last image if they were already reported
+      drain_files(&mut images)?;
+
+      Ok(images)
+    })
+  }
+
+  /// Wait until the camera reports a new file, skipping all other events
+  ///
+  /// Returns an error of kind [`ErrorKind::Timeout`](crate::error::ErrorKind::Timeout)
+  /// if no file was added within `timeout`.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// camera.trigger_capture().wait()?;
+  /// let file = camera.wait_for_file(Duration::from_secs(10)).wait()?;
+  /// println!("Captured {}", file);
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn wait_for_file(&self, timeout: Duration) -> Task<Result<CameraFilePath>> {
+    self.wait_for(timeout, |event| match event {
+      CameraEvent::NewFile(file) => Some(file),
+      _ => None,
+    })
+  }
+
+  /// Wait until the camera reports that a capture completed, skipping all other events
+  ///
+  /// Note that files added while waiting are skipped as well. Returns an error of kind
+  /// [`ErrorKind::Timeout`](crate::error::ErrorKind::Timeout) if no capture completed
+  /// within `timeout`.
+  pub fn wait_for_capture_complete(&self, timeout: Duration) -> Task<Result<()>> {
+    self.wait_for(timeout, |event| match event {
+      CameraEvent::CaptureComplete => Some(()),
+      _ => None,
+    })
+  }
+
+  /// Wait for the first event `filter` returns a value for
+  fn wait_for<T: 'static + Send>(
+    &self,
+    timeout: Duration,
+    mut filter: impl FnMut(CameraEvent) -> Option<T> + Send + 'static,
+  ) -> Task<Result<T>> {
+    let camera = self.clone();
+    let deadline = deadline_after(timeout);
+
+    Task::blocking(move |cancel| loop {
+      match camera.poll_event_until(deadline, cancel)? {
+        CameraEvent::Timeout => return Err(Error::new(libgphoto2_sys::GP_ERROR_TIMEOUT, None)),
+        event => {
+          if let Some(value) = filter(event) {
+            return Ok(value);
+          }
+        }
+      }
+    })
+  }
+
+  /// Move the focus of the lens by a [`FocusStep`]
+  ///
+  /// Most cameras can only move the focus while live view is enabled.
+  pub fn focus(&self, step: FocusStep) -> Result<()> {
+    focus::drive_focus_step(self, step)
+  }
+
+  /// Typed access to common settings like ISO, aperture and shutter speed
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// let settings = camera.settings();
+  /// println!("ISO {}, f/{}, {}", settings.iso()?, settings.aperture()?, settings.shutter_speed()?);
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn settings(&self) -> Settings {
+    Settings::new(self.clone())
+  }
+
+  /// Get where captured images are stored
+  pub fn capture_target(&self) -> Result<CaptureTarget> {
+    self.settings().capture_target()
+  }
+
+  /// Set where captured images are stored
+  ///
+  /// With [`CaptureTarget::InternalRam`] images are not written to the memory card,
+  /// so they must be downloaded after the capture.
+  ///
+  /// ```no_run
+  /// use gphoto2::{settings::CaptureTarget, Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// camera.set_capture_target(CaptureTarget::InternalRam)?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn set_capture_target(&self, target: CaptureTarget) -> Result<()> {
+    self.settings().set_capture_target(target)
+  }
+
+  /// Get the date and time of the camera clock
+  pub fn datetime(&self) -> Result<SystemTime> {
+    self.settings().datetime()
+  }
+
+  /// Set the camera clock, e.g. to the current time of the host
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  /// use std::time::SystemTime;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// camera.sync_datetime(SystemTime::now())?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn sync_datetime(&self, time: SystemTime) -> Result<()> {
+    self.settings().sync_datetime(time)
+  }
+
+  /// Set the GPS position written to new images, see [`Settings::set_gps`]
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// camera.set_gps(48.8584, 2.2945, 35.0)?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn set_gps(&self, latitude: f64, longitude: f64, altitude: f64) -> Result<()> {
+    self.settings().set_gps(latitude, longitude, altitude)
+  }
+
+  /// Control the power zoom of the lens with a [`ZoomCommand`]
+  pub fn zoom(&self, command: ZoomCommand) -> Result<()> {
+    zoom::zoom(self, command)
+  }
+
+  /// Close the connection to the camera
+  ///
+  /// This releases the device (e.g. the USB interface) so other applications can access it,
+  /// the [`Camera`] stays usable: the next operation on it reconnects automatically.
+  ///
+  /// Dropping the last handle to the camera also closes the connection.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let camera = context.autodetect_camera().wait()?;
+  ///
+  /// camera.exit().wait()?;
+  /// // The camera can now be used by other applications
+  ///
+  /// // Reconnects to the camera
+  /// let file = camera.capture_image().wait()?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn exit(&self) -> Task<Result<()>> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_exit(*camera, *context)?);
+
+        Ok(())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Close the connection and connect to the camera again, e.g. after the device was lost
+  ///
+  /// The camera is detected again on the port it was connected to before, or by its serial
+  /// number, since the USB port path changes when the camera is plugged in again. The serial
+  /// number is read before closing the connection, or earlier by
+  /// [`Camera::set_auto_reconnect`], since it can't be read once the device is gone.
+  ///
+  /// If the serial number is unknown, another camera of the same model is only used if it's
+  /// the only one connected, otherwise an error of kind
+  /// [`ErrorKind::ModelNotFound`](crate::error::ErrorKind::ModelNotFound) is returned.
+  /// All clones of this camera use the new connection.
+  pub fn reconnect(&self) -> Result<()> {
+    let model = self.abilities().model().into_owned();
+    let previous_port = self.port_info().map(|port_info| port_info.path()).ok();
+    let serial_number = self.serial_number();
+
+    // The device is usually gone, so closing the old connection may fail
+    if let Err(error) = self.exit().wait() {
+      log::debug!("Could not exit camera before reconnecting: {}", error);
+    }
+
+    let mut ports: Vec<_> = self
+      .context
+      .list_cameras()
+      .wait()?
+      .filter(|descriptor| descriptor.model == model)
+      .map(|descriptor| descriptor.port)
+      .collect();
+
+    // Try the previous port first
+    ports.sort_by_key(|port| Some(port) != previous_port.as_ref());
+
+    let Some(serial_number) = serial_number else {
+      return self.connect_port(reconnect_port(&ports, previous_port.as_deref(), &model)?);
+    };
+
+    for port in &ports {
+      if let Err(error) = self.connect_port(port) {
+        // The camera may already be used by another handle
+        log::debug!("Could not connect to {} at {}: {}", model, port, error);
+        continue;
+      }
+
+      if self.read_serial_number().as_deref() == Some(serial_number.as_str()) {
+        return Ok(());
+      }
+
+      if let Err(error) = self.exit().wait() {
+        log::debug!("Could not exit camera {} at {}: {}", model, port, error);
+      }
+    }
+
+    Err(Error::new(
+      libgphoto2_sys::GP_ERROR_MODEL_NOT_FOUND,
+      Some(format!("Could not find {model} with serial number {serial_number} to reconnect")),
+    ))
+  }
+
+  /// Connect to the camera on the port with the path `port`
+  fn connect_port(&self, port: &str) -> Result<()> {
+    let port = port.to_owned();
+    let camera = self.camera;
+    let context = self.context.inner;
+    let task_context = self.context.clone();
+
+    unsafe {
+      Task::new(move || {
+        let port_info_list = PortInfoList::new_inner(&task_context)?;
+        let port_info = port_info_list.find_path(&port)?;
+
+        try_gp_internal!(gp_camera_set_port_info(*camera, port_info.inner)?);
+        try_gp_internal!(gp_camera_init(*camera, *context)?);
+
+        Ok(())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+    .wait()?;
+
+    // Errors should name the new port
+    self.cache.lock().unwrap().identity = None;
+
+    Ok(())
+  }
+
+  /// Get the timeout of the port for single transfers
+  pub fn timeout(&self) -> Task<Result<Duration>> {
+    let camera = self.camera;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_port_get_timeout((**camera).port, &out timeout)?);
+
+        Ok(Duration::from_millis(timeout.try_into()?))
+      })
+    }
+    .for_camera(self)
+  }
+
+  /// Set the timeout of the port for single transfers
+  ///
+  /// Longer timeouts help with slow operations, e.g. large downloads over USB hubs,
+  /// shorter ones with quick health checks. Drivers set their own timeout when connecting,
+  /// so it has to be set again after [`Camera::reconnect`] or [`Camera::exit`].
+  pub fn set_timeout(&self, timeout: Duration) -> Task<Result<()>> {
+    let camera = self.camera;
+
+    unsafe {
+      Task::new(move || {
+        let timeout = timeout.as_millis().try_into()?;
+        try_gp_internal!(gp_port_set_timeout((**camera).port, timeout)?);
+
+        Ok(())
+      })
+    }
+    .for_camera(self)
+  }
+
+  /// Get if [`Camera::with_retry`] reconnects automatically when the device was lost
+  pub fn auto_reconnect(&self) -> bool {
+    self.auto_reconnect
+  }
+
+  /// Reconnect automatically in [`Camera::with_retry`] when the device was lost
+  ///
+  /// If an operation still fails with an I/O error after all retries, the camera is
+  /// [reconnected](Camera::reconnect) and the operation is retried again.
+  /// The setting is copied to clones of this camera created afterwards.
+  ///
+  /// Enabling it reads the serial number of the camera, which is used to find the camera
+  /// again once the device was lost.
+  pub fn set_auto_reconnect(&mut self, auto_reconnect: bool) {
+    if auto_reconnect {
+      self.serial_number();
+    }
+
+    self.auto_reconnect = auto_reconnect;
+  }
+
+  /// Capture a preview image
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let camera = context.autodetect_camera().wait()?;
+  ///
+  /// let image_preview = camera.capture_preview().wait()?;
+  /// println!("Preview name: {}", image_preview.name());
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn capture_preview(&self) -> Task<Result<CameraFile>> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        let camera_file = CameraFile::new()?;
+
+        try_gp_internal!(gp_camera_capture_preview(*camera, *camera_file.inner, *context)?);
+
+        Ok(camera_file)
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Create a [`Timelapse`] capturing every `interval`
+  pub fn timelapse(&self, interval: Duration) -> Timelapse {
+    Timelapse::new(self, interval)
+  }
+
+  /// Start a live view stream
+  ///
+  /// Enables the viewfinder (if the camera has one) and returns a [`PreviewStream`]
+  /// which captures preview frames while reusing its buffers.
+  pub fn preview_stream(&self) -> Result<PreviewStream> {
+    PreviewStream::new(self.clone())
+  }
+
+  /// Get the camera's [`Abilities`]
+  ///
+  /// The abilities contain information about the driver used, permissions and camera model
+  pub fn abilities(&self) -> Abilities {
+    let mut inner = UninitBox::uninit();
+
+    try_gp_internal!(gp_camera_get_abilities(*self.camera, inner.as_mut_ptr()).unwrap());
+
+    Abilities { inner: unsafe { inner.assume_init() } }
+  }
+
+  fn operations(&self) -> CameraOperations {
+    if let Some(operations) = &self.cache.lock().unwrap().operations {
+      return operations.clone();
+    }
+
+    let operations = self.abilities().camera_operations();
+    self.cache.lock().unwrap().operations = Some(operations.clone());
+    operations
+  }
+
+  /// Get the [`RetryPolicy`] used by [`Camera::with_retry`]
+  pub fn retry_policy(&self) -> RetryPolicy {
+    self.retry_policy
+  }
+
+  /// Set the [`RetryPolicy`] used by [`Camera::with_retry`]
+  ///
+  /// The policy is copied to clones of this camera created afterwards.
+  pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+    self.retry_policy = retry_policy;
+  }
+
+  /// Run an operation, retrying it according to the [`RetryPolicy`] of the camera
+  /// if it fails because the camera is busy or with a transient I/O error
+  ///
+  /// The operation is called again for every attempt and the task is waited for.
+  /// See [`Camera::set_auto_reconnect`] to also reconnect when the device was lost.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let file = camera.with_retry(|camera| camera.capture_image())?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn with_retry<T>(&self, mut operation: impl FnMut(&Camera) -> Task<Result<T>>) -> Result<T>
+  where
+    T: 'static + Send,
+  {
+    match self.retry_policy.run(|| operation(self).wait()) {
+      Err(error) if self.auto_reconnect && error.is_io() => {
+        log::warn!("Reconnecting after I/O error: {}", error);
+
+        self.reconnect()?;
+        self.retry_policy.run(|| operation(self).wait())
+      }
+      result => result,
+    }
+  }
+
+  /// Model and port of the camera, used to attribute errors
+  pub(crate) fn identity(&self) -> Arc<str> {
+    if let Some(identity) = &self.cache.lock().unwrap().identity {
+      return identity.clone();
+    }
+
+    let model = self.abilities().model().into_owned();
+    let identity: Arc<str> = match self.port_info() {
+      Ok(port_info) => format!("{} at {}", model, port_info.path()).into(),
+      Err(_) => model.into(),
+    };
+
+    self.cache.lock().unwrap().identity = Some(identity.clone());
+    identity
+  }
+
+  /// Serial number of the camera, from the configuration or the summary
+  ///
+  /// Cached once it was read, so it's still known after the device was lost.
+  fn serial_number(&self) -> Option<String> {
+    if let Some(serial_number) = &self.cache.lock().unwrap().serial_number {
+      return Some(serial_number.clone());
+    }
+
+    let serial_number = self.read_serial_number()?;
+
+    self.cache.lock().unwrap().serial_number = Some(serial_number.clone());
+    Some(serial_number)
+  }
+
+  /// Read the serial number of the camera, without using the cache
+  fn read_serial_number(&self) -> Option<String> {
+    let serial_number = match self.config_key::<TextWidget>("serialnumber").wait() {
+      Ok(widget) => Some(widget.value()),
+      Err(_) => self.summary().ok().and_then(|summary| {
+        summary
+          .lines()
+          .find_map(|line| line.trim().strip_prefix("Serial Number:").map(str::to_owned))
+      }),
+    };
+
+    serial_number.map(|serial| serial.trim().to_owned()).filter(|serial| !serial.is_empty())
+  }
+
+  /// Check if the camera can capture images
+  ///
+  /// This and the other `can_*` methods read the [`Abilities`] only once and cache them.
+  pub fn can_capture_image(&self) -> bool {
+    self.operations().capture_image()
+  }
+
+  /// Check if the camera can capture videos
+  pub fn can_capture_video(&self) -> bool {
+    self.operations().capture_video()
+  }
+
+  /// Check if the camera can capture audio
+  pub fn can_capture_audio(&self) -> bool {
+    self.operations().capture_audio()
+  }
+
+  /// Check if the camera can capture previews
+  pub fn can_preview(&self) -> bool {
+    self.operations().capture_preview()
+  }
+
+  /// Check if the camera can be configured
+  pub fn can_config(&self) -> bool {
+    self.operations().configure()
+  }
+
+  /// Check if the camera can trigger captures
+  pub fn can_trigger_capture(&self) -> bool {
+    self.operations().trigger_capture()
+  }
+
+  /// Summary of the cameras model, settings, capabilities, etc.
+  ///
+  /// Like [`Camera::about`] and [`Camera::manual`], this runs on the libgphoto2 thread
+  /// and blocks until it's done.
+  pub fn summary(&self) -> Result<String> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_summary(*camera, &out summary, *context)?);
+
+        Ok(char_slice_to_cow(&summary.text).into_owned())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+    .wait()
+  }
+
+  /// Get about information about the camera#
+  pub fn about(&self) -> Result<String> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_about(*camera, &out about, *context)?);
+
+        Ok(char_slice_to_cow(&about.text).into_owned())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+    .wait()
+  }
+
+  /// Get the manual of the camera
+  ///
+  /// Not all cameras support this, and will return NotSupported
+  pub fn manual(&self) -> Result<String> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_manual(*camera, &out manual, *context)?);
+
+        Ok(char_slice_to_cow(&manual.text).into_owned())
+      })
+    }
+    .context(context)
+    .for_camera(self)
+    .wait()
+  }
+
+  /// List of storages available on the camera
+  pub fn storages(&self) -> Task<Result<Vec<StorageInfo>>> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_storageinfo(
+          *camera,
+          &out storages_ptr,
+          &out storages_len,
+          *context
+        )?);
+
+        // The array is allocated by libgphoto2, so the storages are copied into a Rust owned
+        // Vec. Cameras without storages may return a null pointer.
+        let result = match usize::try_from(storages_len) {
+          Ok(len) if !storages_ptr.is_null() => {
+            // We can cast pointer safely because StorageInfo is repr(transparent).
+            std::slice::from_raw_parts(storages_ptr.cast::<StorageInfo>(), len).to_vec()
+          }
+          _ => Vec::new(),
+        };
+
+        // Must be freed using libc deallocator rather than Rust one, also if the length is invalid.
+        libc::free(storages_ptr.cast());
+
+        Ok(result)
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Filesystem actions
+  pub fn fs(&self) -> CameraFS<'_> {
+    CameraFS::new(self)
+  }
+
+  /// Waits for the next event on the camera until `deadline`
+  ///
+  /// [`Camera::wait_event`] is a single poll of the driver, which may return
+  /// [`CameraEvent::Timeout`] before the timeout elapsed. This keeps polling and only returns
+  /// [`CameraEvent::Timeout`] once the deadline passed.
+  ///
+  /// The polls run on their own thread, so other cameras can use the libgphoto2 thread
+  /// between them.
+  ///
+  /// ```no_run
+  /// use gphoto2::{camera::CameraEvent, Context, Result};
+  /// use std::time::{Duration, Instant};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let deadline = Instant::now() + Duration::from_secs(10);
+  ///
+  /// loop {
+  ///   match camera.wait_event_until(deadline).wait()? {
+  ///     CameraEvent::NewFile(file) => println!("New file {}", file),
+  ///     CameraEvent::Timeout => break,
+  ///     _ => {}
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn wait_event_until(&self, deadline: Instant) -> Task<Result<CameraEvent>> {
+    let camera = self.clone();
+
+    Task::blocking(move |cancel| camera.poll_event_until(deadline, cancel))
+  }
+
+  /// Waits for the next event on the camera for up to `timeout` in total,
+  /// see [`Camera::wait_event_until`]
+  pub fn wait_event_total(&self, timeout: Duration) -> Task<Result<CameraEvent>> {
+    self.wait_event_until(deadline_after(timeout))
+  }
+
+  /// Blocking implementation of [`Camera::wait_event_until`]
+  fn poll_event_until(&self, deadline: Instant, cancel: &CancellationToken) -> Result<CameraEvent> {
+    // Not all drivers check for cancellation while waiting for an event, keep the polls short
+    // so a cancelled task stops soon
+    const MAX_POLL_TIMEOUT: Duration = Duration::from_secs(1);
+
+    loop {
+      let remaining = deadline.saturating_duration_since(Instant::now());
+
+      match self.wait_event(remaining.min(MAX_POLL_TIMEOUT)).wait_with(cancel)? {
+        CameraEvent::Timeout if Instant::now() < deadline => {}
+        event => return Ok(event),
+      }
+    }
+  }
+
+  /// Waits for an event on the camera until timeout
+  pub fn wait_event(&self, timeout: Duration) -> Task<Result<CameraEvent>> {
+    use libgphoto2_sys::CameraEventType;
+
+    let duration_milliseconds = timeout.as_millis();
+
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_wait_for_event(
+          *camera,
+          duration_milliseconds.try_into()?,
+          &out event_type,
+          &out event_data,
+          *context
+        )?);
+
+        let event = match event_type {
+          CameraEventType::GP_EVENT_UNKNOWN => CameraEvent::Unknown(if event_data.is_null() {
+            String::new()
+          } else {
+            chars_to_string(event_data.cast::<c_char>())
+          }),
+          CameraEventType::GP_EVENT_TIMEOUT => CameraEvent::Timeout,
+          CameraEventType::GP_EVENT_FILE_ADDED
+          | CameraEventType::GP_EVENT_FOLDER_ADDED
+          | CameraEventType::GP_EVENT_FILE_CHANGED => {
+            if event_data.is_null() {
+              return Err(Error::new(
+                libgphoto2_sys::GP_ERROR_CORRUPTED_DATA,
+                Some("File event without file path".to_owned()),
+              ));
+            }
+
+            let file_path = CameraFilePath {
+              inner: Box::new(*event_data.cast::<libgphoto2_sys::CameraFilePath>()),
+            };
+
+            match event_type {
+              CameraEventType::GP_EVENT_FILE_ADDED => CameraEvent::NewFile(file_path),
+              CameraEventType::GP_EVENT_FOLDER_ADDED => CameraEvent::NewFolder(file_path),
+              CameraEventType::GP_EVENT_FILE_CHANGED => CameraEvent::FileChanged(file_path),
+              _ => unreachable!(),
+            }
+          }
+          CameraEventType::GP_EVENT_CAPTURE_COMPLETE => CameraEvent::CaptureComplete,
+        };
+
+        // Event data is allocated by libgphoto2 and must be freed using the libc deallocator,
+        // this is a no-op for events without data.
+        libc::free(event_data);
+
+        Ok(event)
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Iterate over camera events
+  ///
+  /// Each iteration waits up to `timeout` for an event.
+  ///
+  /// ```no_run
+  /// use gphoto2::{camera::CameraEvent, Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// for event in camera.events(Duration::from_secs(1)).skip_timeouts() {
+  ///   if let CameraEvent::NewFile(file) = event? {
+  ///     println!("New file: {}", file.name());
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn events(&self, timeout: Duration) -> CameraEventIter {
+    CameraEventIter { camera: self.clone(), timeout, skip_timeouts: false }
+  }
+
+  /// Wait for events on a background thread and send them to a channel
+  ///
+  /// The thread waits for up to `poll_timeout` at a time, [`CameraEvent::Timeout`] events are
+  /// not sent. Errors are sent to the channel as well, if waiting keeps failing (e.g. because
+  /// the camera was disconnected) the thread stops. This allows GUI applications to poll the
+  /// channel in their frame loop without blocking.
+  ///
+  /// ```no_run
+  /// use gphoto2::{camera::CameraEvent, Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let (events, pump) = camera.spawn_event_pump(Duration::from_millis(100))?;
+  ///
+  /// // In the frame loop
+  /// for event in events.try_iter() {
+  ///   if let Ok(CameraEvent::NewFile(file)) = event {
+  ///     println!("New file {}", file);
+  ///   }
+  /// }
+  ///
+  /// pump.shutdown();
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn spawn_event_pump(
+    &self,
+    poll_timeout: Duration,
+  ) -> Result<(Receiver<Result<CameraEvent>>, EventPump)> {
+    let (sender, events) = crossbeam_channel::unbounded();
+    let pump = self.spawn_pump(poll_timeout, move |event| sender.send(event).is_ok())?;
+
+    Ok((events, pump))
+  }
+
+  /// Call `callback` with every event of the camera on a background thread
+  ///
+  /// Like [`Camera::spawn_event_pump`] with a poll timeout of 100ms, but the events are passed
+  /// to the callback. Errors while waiting for events are logged. The callback is called until
+  /// the returned [`EventPump`] is dropped, so it must be kept alive.
+  ///
+  /// ```no_run
+  /// use gphoto2::{
+  ///   camera::{CameraEvent, CameraEventKind},
+  ///   Context, Result,
+  /// };
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// let _subscription = camera.on_event_kinds(&[CameraEventKind::NewFile], |event| {
+  ///   if let CameraEvent::NewFile(file) = event {
+  ///     println!("New file {}", file);
+  ///   }
+  /// })?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn on_event(&self, callback: impl FnMut(CameraEvent) + Send + 'static) -> Result<EventPump> {
+    self.on_event_kinds(
+      &[
+        CameraEventKind::Unknown,
+        CameraEventKind::NewFile,
+        CameraEventKind::FileChanged,
+        CameraEventKind::NewFolder,
+        CameraEventKind::CaptureComplete,
+      ],
+      callback,
+    )
+  }
+
+  /// Like [`Camera::on_event`], but only calls `callback` with events of the given kinds
+  pub fn on_event_kinds(
+    &self,
+    kinds: &[CameraEventKind],
+    mut callback: impl FnMut(CameraEvent) + Send + 'static,
+  ) -> Result<EventPump> {
+    let kinds = kinds.to_vec();
+
+    self.spawn_pump(Duration::from_millis(100), move |event| {
+      match event {
+        Ok(event) if kinds.contains(&event.kind()) => callback(event),
+        Ok(_) => {}
+        Err(error) => log::warn!("Could not wait for camera events: {}", error),
+      }
+
+      true
+    })
+  }
+
+  fn spawn_pump(
+    &self,
+    poll_timeout: Duration,
+    forward: impl FnMut(Result<CameraEvent>) -> bool + Send + 'static,
+  ) -> Result<EventPump> {
+    let (stop, stop_receiver) = crossbeam_channel::bounded(0);
+    let camera = self.clone();
+
+    let thread = thread::Builder::new()
+      .name("gphoto2-events".to_owned())
+      .spawn(move || worker::event_worker(camera, poll_timeout, stop_receiver, None, forward))?;
+
+    Ok(EventPump { stop: Some(stop), thread: Some(thread) })
+  }
+
+  /// Port used to connect to the camera
+  pub fn port_info(&self) -> Result<PortInfo<'_>> {
+    try_gp_internal!(gp_camera_get_port_info(*self.camera, &out port_info)?);
+
+    Ok(unsafe { PortInfo::new(port_info) })
+  }
+
+  /// Get the entire camera configuration tree
+  pub fn config(&self) -> Task<Result<GroupWidget>> {
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
+
+        Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Get a single configuration by name.
+  /// Pass either a specific widget type as a generic parameter or [`Widget`]
+  /// if you're not sure what this config represents.
+  // TODO: Get rid of the 'static lifetime
+  pub fn config_key<T: TryFrom<Widget> + 'static + Send>(&self, key: &str) -> Task<Result<T>>
+  where
+    Error: From<T::Error>,
+  {
+    let key = key.to_owned();
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_single_config(
+          *camera,
+          to_c_string!(&*key),
+          &out widget,
+          *context
+        )?);
+
+        Ok(Widget::new_owned(BackgroundPtr(widget)).try_into()?)
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+
+  /// Get a single configuration by its path, e.g. `/main/imgsettings/iso`
+  ///
+  /// See [`GroupWidget::get_child_by_path`].
+  pub fn config_path<T: TryFrom<Widget> + 'static + Send>(&self, path: &str) -> Task<Result<T>>
+  where
+    Error: From<T::Error>,
+  {
+    let path = path.to_owned();
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-        Ok(())
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        Ok(root.get_child_by_path(&path)?.try_into()?)
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
-  /// Capture a preview image
-  ///
-  /// ```no_run
-  /// use gphoto2::{Context, Result};
-  ///
-  /// # fn main() -> Result<()> {
-  /// let context = Context::new()?;
-  /// let camera = context.autodetect_camera().wait()?;
+  /// List all configuration leaves with their full path, type, value and choices
   ///
-  /// let image_preview = camera.capture_preview().wait()?;
-  /// println!("Preview name: {}", image_preview.name());
-  /// # Ok(())
-  /// # }
-  /// ```
-  pub fn capture_preview(&self) -> Task<Result<CameraFile>> {
+  /// This is the structured equivalent of `gphoto2 --list-all-config`,
+  /// see [`ConfigEntry`].
+  pub fn list_config(&self) -> Task<Result<Vec<ConfigEntry>>> {
     let camera = self.camera;
     let context = self.context.inner;
 
     unsafe {
       Task::new(move || {
-        let camera_file = CameraFile::new()?;
-
-        try_gp_internal!(gp_camera_capture_preview(*camera, *camera_file.inner, *context)?);
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-        Ok(camera_file)
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        Ok(config::list_entries(&root))
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
-  /// Get the camera's [`Abilities`]
-  ///
-  /// The abilities contain information about the driver used, permissions and camera model
-  pub fn abilities(&self) -> Abilities {
-    let mut inner = UninitBox::uninit();
-
-    try_gp_internal!(gp_camera_get_abilities(*self.camera, inner.as_mut_ptr()).unwrap());
-
-    Abilities { inner: unsafe { inner.assume_init() } }
-  }
-
-  /// Summary of the cameras model, settings, capabilities, etc.
-  pub fn summary(&self) -> Result<String> {
-    try_gp_internal!(gp_camera_get_summary(*self.camera, &out summary, *self.context.inner)?);
-
-    Ok(char_slice_to_cow(&summary.text).into_owned())
-  }
-
-  /// Get about information about the camera#
-  pub fn about(&self) -> Result<String> {
-    try_gp_internal!(gp_camera_get_about(*self.camera, &out about, *self.context.inner)?);
-
-    Ok(char_slice_to_cow(&about.text).into_owned())
-  }
-
-  /// Get the manual of the camera
+  /// Set multiple configuration values from strings in a single call to the camera
   ///
-  /// Not all cameras support this, and will return NotSupported
-  pub fn manual(&self) -> Result<String> {
-    try_gp_internal!(gp_camera_get_manual(*self.camera, &out manual, *self.context.inner)?);
-
-    Ok(char_slice_to_cow(&manual.text).into_owned())
-  }
-
-  /// List of storages available on the camera
-  pub fn storages(&self) -> Task<Result<Vec<StorageInfo>>> {
+  /// Keys are either widget names (e.g. `iso`) or full paths (e.g. `/main/imgsettings/iso`),
+  /// values are parsed according to the widget type, see [`Widget::set_from_str`].
+  /// Keys that cannot be resolved or parsed are skipped and returned as [`ConfigKeyError`]s,
+  /// the remaining values are still applied.
+  pub fn apply_config<K, V>(
+    &self,
+    values: impl IntoIterator<Item = (K, V)>,
+  ) -> Task<Result<Vec<ConfigKeyError>>>
+  where
+    K: AsRef<str>,
+    V: AsRef<str>,
+  {
+    let values: Vec<(String, String)> = values
+      .into_iter()
+      .map(|(key, value)| (key.as_ref().to_owned(), value.as_ref().to_owned()))
+      .collect();
     let camera = self.camera;
     let context = self.context.inner;
 
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_camera_get_storageinfo(
-          *camera,
-          &out storages_ptr,
-          &out storages_len,
-          *context
-        )?);
-
-        let storages = std::slice::from_raw_parts(
-          // We can cast pointer safely because StorageInfo is repr(transparent).
-          storages_ptr.cast::<StorageInfo>(),
-          storages_len.try_into()?,
-        );
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-        let result = storages.to_vec();
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        config::clear_changed(&root);
+        let errors = config::set_values(&root, &values);
 
-        // Must be freed using libc deallocator rather than Rust one.
-        libc::free(storages_ptr.cast());
+        if errors.len() < values.len() {
+          try_gp_internal!(gp_camera_set_config(*camera, *root.inner, *context)?);
+        }
 
-        Ok(result)
+        Ok(errors)
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
-  /// Filesystem actions
-  pub fn fs(&self) -> CameraFS<'_> {
-    CameraFS::new(self)
-  }
-
-  /// Waits for an event on the camera until timeout
-  pub fn wait_event(&self, timeout: Duration) -> Task<Result<CameraEvent>> {
-    use libgphoto2_sys::CameraEventType;
+  /// Start a [`ConfigTransaction`] to change multiple settings with a single write
+  ///
+  /// This fetches the full configuration and blocks until it is available.
+  pub fn transaction(&self) -> Result<ConfigTransaction> {
+    let root = self.config().wait()?;
+    config::clear_changed(&root);
 
-    let duration_milliseconds = timeout.as_millis();
+    Ok(ConfigTransaction::new(self.clone(), root))
+  }
 
+  /// Take a [`ConfigSnapshot`] of all writable settings
+  pub fn config_snapshot(&self) -> Task<Result<ConfigSnapshot>> {
     let camera = self.camera;
     let context = self.context.inner;
 
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_camera_wait_for_event(
-          *camera,
-          duration_milliseconds.try_into()?,
-          &out event_type,
-          &out event_data,
-          *context
-        )?);
-
-        Ok(match event_type {
-          CameraEventType::GP_EVENT_UNKNOWN => {
-            let s = chars_to_string(event_data.cast::<c_char>());
-
-            libc::free(event_data);
-
-            CameraEvent::Unknown(s)
-          }
-          CameraEventType::GP_EVENT_TIMEOUT => CameraEvent::Timeout,
-          CameraEventType::GP_EVENT_FILE_ADDED
-          | CameraEventType::GP_EVENT_FOLDER_ADDED
-          | CameraEventType::GP_EVENT_FILE_CHANGED => {
-            let file_path = CameraFilePath {
-              inner: Box::new(*event_data.cast::<libgphoto2_sys::CameraFilePath>()),
-            };
-
-            libc::free(event_data);
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-            match event_type {
-              CameraEventType::GP_EVENT_FILE_ADDED => CameraEvent::NewFile(file_path),
-              CameraEventType::GP_EVENT_FOLDER_ADDED => CameraEvent::NewFolder(file_path),
-              CameraEventType::GP_EVENT_FILE_CHANGED => CameraEvent::FileChanged(file_path),
-              _ => unreachable!(),
-            }
-          }
-          CameraEventType::GP_EVENT_CAPTURE_COMPLETE => CameraEvent::CaptureComplete,
-        })
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        Ok(ConfigSnapshot::from_root(&root))
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
-  /// Port used to connect to the camera
-  pub fn port_info(&self) -> Result<PortInfo<'_>> {
-    try_gp_internal!(gp_camera_get_port_info(*self.camera, &out port_info)?);
-
-    Ok(unsafe { PortInfo::new(port_info) })
-  }
-
-  /// Get the entire camera configuration tree
-  pub fn config(&self) -> Task<Result<GroupWidget>> {
+  /// Compare a [`ConfigSnapshot`] with the current configuration
+  ///
+  /// The snapshot is treated as the old state, the camera as the new state.
+  pub fn config_diff(&self, snapshot: &ConfigSnapshot) -> Task<Result<ConfigDiff>> {
+    let snapshot = snapshot.clone();
     let camera = self.camera;
     let context = self.context.inner;
 
@@ -322,37 +1622,41 @@ impl Camera {
       Task::new(move || {
         try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-        Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        Ok(snapshot.diff(&ConfigSnapshot::from_root(&root)))
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
-  /// Get a single configuration by name.
-  /// Pass either a specific widget type as a generic parameter or [`Widget`]
-  /// if you're not sure what this config represents.
-  // TODO: Get rid of the 'static lifetime
-  pub fn config_key<T: TryFrom<Widget> + 'static + Send>(&self, key: &str) -> Task<Result<T>>
-  where
-    Error: From<T::Error>,
-  {
-    let key = key.to_owned();
+  /// Restore the settings of a [`ConfigSnapshot`], which may come from a different camera
+  ///
+  /// Only values which differ from the current configuration are set, all in a single call
+  /// to the camera. Settings which don't exist on this camera or cannot be set are skipped
+  /// and returned as [`ConfigKeyError`]s.
+  pub fn restore_snapshot(&self, snapshot: &ConfigSnapshot) -> Task<Result<Vec<ConfigKeyError>>> {
+    let snapshot = snapshot.clone();
     let camera = self.camera;
     let context = self.context.inner;
 
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_camera_get_single_config(
-          *camera,
-          to_c_string!(&*key),
-          &out widget,
-          *context
-        )?);
+        try_gp_internal!(gp_camera_get_config(*camera, &out root_widget, *context)?);
 
-        Ok(Widget::new_owned(BackgroundPtr(widget)).try_into()?)
+        let root = Widget::new_owned(BackgroundPtr(root_widget)).try_into::<GroupWidget>()?;
+        config::clear_changed(&root);
+        let (changed, errors) = snapshot.apply(&root);
+
+        if changed > 0 {
+          try_gp_internal!(gp_camera_set_config(*camera, *root.inner, *context)?);
+        }
+
+        Ok(errors)
       })
     }
     .context(context)
+    .for_camera(self)
   }
 
   /// Apply a full config object to the camera.
@@ -369,9 +1673,13 @@ impl Camera {
       })
     }
     .context(self.context.inner)
+    .for_camera(self)
   }
 
   /// Set a single configuration widget to the camera
+  ///
+  /// Returns an error with [`ErrorKind::NotSupported`](crate::error::ErrorKind::NotSupported)
+  /// if the widget is [read-only](WidgetBase::readonly).
   pub fn set_config(&self, config: &WidgetBase) -> Task<Result<()>> {
     let config = config.clone();
     let camera = self.camera;
@@ -379,6 +1687,8 @@ impl Camera {
 
     unsafe {
       Task::new(move || {
+        config.check_writable()?;
+
         try_gp_internal!(gp_camera_set_single_config(
           *camera,
           to_c_string!(config.name()),
@@ -390,9 +1700,106 @@ impl Camera {
       })
     }
     .context(context)
+    .for_camera(self)
+  }
+
+  /// Set a single configuration widget to the camera, unless the camera already has its value
+  ///
+  /// Reads the current value first and only writes if it differs, since writing identical
+  /// values still triggers slow UI refreshes on some cameras and can interrupt video recording.
+  /// Returns `true` if the value was written. Widgets without a comparable value
+  /// (e.g. buttons) are always written.
+  pub fn set_config_if_changed(&self, config: &WidgetBase) -> Task<Result<bool>> {
+    let config = config.clone();
+    let camera = self.camera;
+    let context = self.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        config.check_writable()?;
+
+        try_gp_internal!(gp_camera_get_single_config(
+          *camera,
+          to_c_string!(config.name()),
+          &out current,
+          *context
+        )?);
+
+        let current = Widget::new_owned(BackgroundPtr(current)).value().ok();
+        let new = Widget::new_shared(config.inner).value().ok();
+
+        if current.is_some() && current == new {
+          return Ok(false);
+        }
+
+        try_gp_internal!(gp_camera_set_single_config(
+          *camera,
+          to_c_string!(config.name()),
+          *config.inner,
+          *context
+        )?);
+
+        Ok(true)
+      })
+    }
+    .context(context)
+    .for_camera(self)
+  }
+}
+
+/// Add a file of a burst to the image it belongs to, or start a new image if less than `count`
+/// images were captured
+///
+/// Files of the same image (e.g. RAW and JPEG) have the same name without extension.
+fn add_burst_file(images: &mut Vec<Vec<CameraFilePath>>, count: usize, file: CameraFilePath) {
+  let stem = |file: &CameraFilePath| {
+    let name = file.name();
+    name.rsplit_once('.').map_or(name.as_ref(), |(stem, _)| stem).to_owned()
+  };
+  let file_stem = stem(&file);
+
+  match images.iter_mut().find(|image| image.first().map(stem).as_ref() == Some(&file_stem)) {
+    Some(image) => image.push(file),
+    None if images.len() < count => images.push(vec![file]),
+    None => log::warn!("Ignoring {}, which was reported after the burst", file),
+  }
+}
+
+/// Deadline `timeout` from now, saturating for very long timeouts
+/// Pick the port to reconnect a camera of `model` to if its serial number is unknown
+///
+/// Another port is only used if it's the only camera of the model, since it can't be
+/// told apart from other cameras of the same model.
+fn reconnect_port<'a>(
+  ports: &'a [String],
+  previous_port: Option<&str>,
+  model: &str,
+) -> Result<&'a str> {
+  if let Some(port) = ports.iter().find(|port| Some(port.as_str()) == previous_port) {
+    return Ok(port);
+  }
+
+  match ports {
+    [port] => Ok(port),
+    [] => Err(Error::new(
+      libgphoto2_sys::GP_ERROR_MODEL_NOT_FOUND,
+      Some(format!("Could not find {model} to reconnect")),
+    )),
+    _ => Err(Error::new(
+      libgphoto2_sys::GP_ERROR_MODEL_NOT_FOUND,
+      Some(format!(
+        "Found several cameras of model {model} to reconnect, but the serial number is unknown"
+      )),
+    )),
   }
 }
 
+fn deadline_after(timeout: Duration) -> Instant {
+  let now = Instant::now();
+
+  now.checked_add(timeout).unwrap_or_else(|| now + Duration::from_secs(u32::MAX.into()))
+}
+
 #[cfg(all(test, feature = "test"))]
 mod tests {
   // Compile-only test to ensure that Camera is Send + Sync.
@@ -401,9 +1808,7 @@ mod tests {
     assert_send_sync::<super::Camera>()
   };
 
-  fn sample_camera() -> super::Camera {
-    crate::sample_context().autodetect_camera().wait().unwrap()
-  }
+  use crate::sample_camera;
 
   #[test]
   fn test_abilities() {
@@ -411,6 +1816,23 @@ mod tests {
     insta::assert_debug_snapshot!(abilities);
   }
 
+  #[test]
+  fn test_drop_clones_in_parallel() {
+    let camera = sample_camera();
+    let clones: Vec<_> = (0..8).map(|_| camera.clone()).collect();
+    drop(camera);
+
+    std::thread::scope(|scope| {
+      for clone in clones {
+        scope.spawn(move || drop(clone));
+      }
+    });
+
+    // The device was released by the last handle, so it can be opened again
+    let camera = sample_camera();
+    assert_eq!(camera.abilities().model(), "Nikon DSC D750");
+  }
+
   #[test]
   fn test_summary() {
     let mut summary = sample_camera().summary().unwrap_or_default();
@@ -489,7 +1911,8 @@ mod tests {
       }
     }
 
-    let camera = sample_camera();
+    // Captures go to the configured capture target
+    let camera = crate::lock_capture_target();
 
     // capture_image should be checked in the same test as fs, because it
     // modifies the filesystem and it's easier to check both in the same test
@@ -540,7 +1963,8 @@ mod tests {
   fn test_config() {
     use crate::widget::{DateWidget, TextWidget};
 
-    let widget_tree = sample_camera().config().wait().unwrap();
+    // The snapshot contains the capture target
+    let widget_tree = crate::lock_capture_target().config().wait().unwrap();
 
     // Some widgets represent dynamic information.
     // Find and fix it up before snapshotting.
@@ -562,4 +1986,153 @@ mod tests {
 
     insta::assert_debug_snapshot!(widget_tree);
   }
+
+  #[test]
+  fn test_config_path() {
+    use crate::widget::RadioWidget;
+
+    let camera = sample_camera();
+
+    let f_number =
+      camera.config_path::<RadioWidget>("/main/capturesettings/f-number").wait().unwrap();
+    assert_eq!(f_number.label(), "F-Number");
+
+    let capture_settings =
+      camera.config_path::<super::GroupWidget>("capturesettings").wait().unwrap();
+    let shutter_speed = capture_settings.get_child_by_path("shutterspeed").unwrap();
+    assert_eq!(shutter_speed.name(), "shutterspeed");
+
+    assert!(camera
+      .config_path::<super::Widget>("/main/capturesettings/f-number/x")
+      .wait()
+      .is_err());
+  }
+
+  #[test]
+  fn test_error_context() {
+    let camera = sample_camera();
+
+    let error = camera.fs().delete_file("/missing", "missing.jpg").wait().unwrap_err();
+    assert_eq!(error.function(), Some("gp_camera_file_delete"));
+    assert!(error.camera().unwrap().starts_with(&*camera.abilities().model()));
+  }
+
+  #[test]
+  fn test_timeout() {
+    use std::time::Duration;
+
+    let camera = sample_camera();
+
+    camera.set_timeout(Duration::from_secs(20)).wait().unwrap();
+    assert_eq!(camera.timeout().wait().unwrap(), Duration::from_secs(20));
+  }
+
+  #[test]
+  fn test_set_from_str() {
+    use crate::error::ErrorKind;
+
+    let camera = sample_camera();
+
+    let f_number = camera.config_key::<super::Widget>("f-number").wait().unwrap();
+    f_number.set_from_str("F/2.8").unwrap();
+    assert_eq!(f_number.value().unwrap(), crate::widget::WidgetValue::Choice("f/2.8".to_owned()));
+
+    let error = f_number.set_from_str("f/1000").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::BadParameters);
+  }
+
+  #[test]
+  fn test_add_burst_file() {
+    use super::add_burst_file;
+    use crate::file::CameraFilePath;
+
+    let file = |folder: &str, name: &str| CameraFilePath::new(folder, name).unwrap();
+    let names = |images: &[Vec<CameraFilePath>]| -> Vec<Vec<String>> {
+      images
+        .iter()
+        .map(|image| image.iter().map(|file| file.name().into_owned()).collect())
+        .collect()
+    };
+
+    let mut images = Vec::new();
+    add_burst_file(&mut images, 2, file("/DCIM", "DSC_0001.NEF"));
+    add_burst_file(&mut images, 2, file("/DCIM", "DSC_0002.NEF"));
+    // RAW and JPEG may be saved to different cards
+    add_burst_file(&mut images, 2, file("/SD2", "DSC_0001.JPG"));
+    add_burst_file(&mut images, 2, file("/SD2", "DSC_0002.JPG"));
+    add_burst_file(&mut images, 2, file("/DCIM", "DSC_0003.NEF"));
+
+    assert_eq!(
+      names(&images),
+      [["DSC_0001.NEF", "DSC_0001.JPG"], ["DSC_0002.NEF", "DSC_0002.JPG"]]
+    );
+  }
+
+  #[test]
+  fn test_reconnect_port() {
+    use super::reconnect_port;
+    use crate::error::ErrorKind;
+
+    let ports = ["usb:001,005".to_owned(), "usb:001,007".to_owned()];
+    let model = "Nikon DSC D750";
+
+    assert_eq!(reconnect_port(&ports, Some("usb:001,007"), model).unwrap(), "usb:001,007");
+    assert_eq!(reconnect_port(&ports[..1], Some("usb:001,002"), model).unwrap(), "usb:001,005");
+    assert_eq!(
+      reconnect_port(&ports, Some("usb:001,002"), model).unwrap_err().kind(),
+      ErrorKind::ModelNotFound
+    );
+    assert_eq!(reconnect_port(&[], None, model).unwrap_err().kind(), ErrorKind::ModelNotFound);
+  }
+
+  #[test]
+  fn test_wait_for_file_timeout() {
+    let error =
+      sample_camera().wait_for_file(std::time::Duration::from_millis(100)).wait().unwrap_err();
+    assert!(error.is_timeout());
+  }
+
+  #[test]
+  fn test_wait_event_total() {
+    use std::time::{Duration, Instant};
+
+    let camera = sample_camera();
+    let start = Instant::now();
+
+    while !matches!(
+      camera.wait_event_total(Duration::from_millis(200)).wait().unwrap(),
+      super::CameraEvent::Timeout
+    ) {}
+
+    assert!(start.elapsed() >= Duration::from_millis(200));
+  }
+
+  #[test]
+  fn test_event_pump() {
+    use std::time::Duration;
+
+    let camera = sample_camera();
+    let (events, pump) = camera.spawn_event_pump(Duration::from_millis(50)).unwrap();
+
+    assert!(pump.is_running());
+    pump.shutdown();
+
+    // All senders are gone once the thread stopped
+    while let Ok(event) = events.recv() {
+      event.unwrap();
+    }
+  }
+
+  #[test]
+  fn test_download_into() {
+    let camera = sample_camera();
+
+    let buffer = Vec::with_capacity(4096);
+    let capacity = buffer.capacity();
+
+    let buffer =
+      camera.fs().download_into("/store_00010001/foo", "blank.jpg", buffer).wait().unwrap();
+    assert_eq!(buffer, libgphoto2_sys::test_utils::SAMPLE_IMAGE);
+    assert_eq!(buffer.capacity(), capacity);
+  }
 }
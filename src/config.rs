@@ -0,0 +1,754 @@
+//! Structured access to the whole camera configuration
+//!
+//! The configuration of a camera is a tree of [`Widget`]s, with sections as inner nodes
+//! and the actual settings as leaves. This module provides a flat view of the leaves,
+//! addressed by their full path (e.g. `/main/capturesettings/f-number`),
+//! similar to `gphoto2 --list-all-config`.
+//!
+//! ## Listing the configuration
+//! ```no_run
+//! use gphoto2::{Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//!
+//! for entry in camera.list_config().wait()? {
+//!   println!("{} ({}): {:?}", entry.path, entry.label, entry.value);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Copying the configuration to another camera
+//! ```no_run
+//! use gphoto2::{Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let cameras = context.list_cameras().wait()?.collect::<Vec<_>>();
+//!
+//! let source = context.get_camera(&cameras[0]).wait()?;
+//! let target = context.get_camera(&cameras[1]).wait()?;
+//!
+//! let snapshot = source.config_snapshot().wait()?;
+//! for error in target.restore_snapshot(&snapshot).wait()? {
+//!   eprintln!("{}", error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Changing multiple settings at once
+//! ```no_run
+//! use gphoto2::{widget::WidgetValue, Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//!
+//! let transaction = camera.transaction()?;
+//! transaction.set_str("iso", "800")?;
+//! transaction.set("viewfinder", WidgetValue::Toggle(true))?;
+//! transaction.commit().wait()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Caching the configuration
+//! ```no_run
+//! use gphoto2::{config::CachedConfig, widget::RadioWidget, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//! let mut config = CachedConfig::new(&camera)?;
+//!
+//! loop {
+//!   let event = camera.wait_event(Duration::from_secs(1)).wait()?;
+//!   config.handle_event(&event);
+//!
+//!   println!("ISO: {}", config.get::<RadioWidget>("iso")?.choice());
+//! }
+//! # }
+//! ```
+//!
+//! ## Applying configuration from strings
+//! ```no_run
+//! use gphoto2::{Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//!
+//! let errors = camera
+//!   .apply_config([("iso", "400"), ("/main/capturesettings/f-number", "f/5.6")])
+//!   .wait()?;
+//!
+//! for error in errors {
+//!   eprintln!("{}", error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  camera::CameraEvent,
+  task::Task,
+  widget::{GroupWidget, Widget, WidgetType, WidgetValue},
+  Camera, Error, Result,
+};
+use std::{
+  collections::{BTreeMap, HashMap, HashSet},
+  fmt,
+  ops::RangeInclusive,
+};
+
+/// Leaf of the camera configuration
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConfigEntry {
+  /// Full path of the widget, e.g. `/main/capturesettings/f-number`
+  pub path: String,
+  /// Name of the widget, which can be used with [`Camera::config_key`](crate::Camera::config_key)
+  pub name: String,
+  /// Human readable label of the widget
+  pub label: String,
+  /// Type of the widget
+  pub widget_type: WidgetType,
+  /// If true, the value cannot be changed
+  pub readonly: bool,
+  /// Current value of the widget, `None` if the value is unknown
+  pub value: Option<WidgetValue>,
+  /// Available choices of radio widgets, empty for other types
+  pub choices: Vec<String>,
+  /// Range and increment step of range widgets
+  pub range: Option<(RangeInclusive<f32>, f32)>,
+}
+
+impl ConfigEntry {
+  pub(crate) fn new(path: String, widget: &Widget) -> Self {
+    let (choices, range) = match widget {
+      Widget::Radio(radio) => (radio.choices_iter().collect(), None),
+      Widget::Range(range) => (Vec::new(), Some(range.range_and_step())),
+      _ => (Vec::new(), None),
+    };
+
+    Self {
+      path,
+      name: widget.name(),
+      label: widget.label(),
+      widget_type: widget.widget_type(),
+      readonly: widget.readonly(),
+      value: widget.value().ok(),
+      choices,
+      range,
+    }
+  }
+}
+
+/// Owned copy of a [`Widget`] and its children
+///
+/// With the `serde` feature, [`Widget`] and [`GroupWidget`] serialize as this struct,
+/// which can be deserialized again, e.g. to render the configuration in a web frontend.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigNode {
+  /// Name of the widget
+  pub name: String,
+  /// Human readable label of the widget
+  pub label: String,
+  /// Type of the widget
+  pub widget_type: WidgetType,
+  /// If true, the value cannot be changed
+  pub readonly: bool,
+  /// Current value of the widget, `None` for sections and unknown values
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub value: Option<WidgetValue>,
+  /// Available choices of radio widgets, empty for other types
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub choices: Vec<String>,
+  /// Range and increment step of range widgets
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub range: Option<(RangeInclusive<f32>, f32)>,
+  /// Children of sections, empty for other types
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  pub children: Vec<ConfigNode>,
+}
+
+impl From<&Widget> for ConfigNode {
+  fn from(widget: &Widget) -> Self {
+    let (choices, range, children) = match widget {
+      Widget::Group(group) => {
+        (Vec::new(), None, group.children_iter().map(|child| Self::from(&child)).collect())
+      }
+      Widget::Radio(radio) => (radio.choices_iter().collect(), None, Vec::new()),
+      Widget::Range(range) => (Vec::new(), Some(range.range_and_step()), Vec::new()),
+      _ => (Vec::new(), None, Vec::new()),
+    };
+
+    Self {
+      name: widget.name(),
+      label: widget.label(),
+      widget_type: widget.widget_type(),
+      readonly: widget.readonly(),
+      value: widget.value().ok(),
+      choices,
+      range,
+      children,
+    }
+  }
+}
+
+impl From<&GroupWidget> for ConfigNode {
+  fn from(group: &GroupWidget) -> Self {
+    Self::from(&Widget::Group(group.clone()))
+  }
+}
+
+/// Call `f` with the full path of every leaf widget below `group`, in tree order
+pub(crate) fn for_each_leaf(group: &GroupWidget, f: &mut impl FnMut(String, Widget)) {
+  fn walk(group: &GroupWidget, prefix: &str, f: &mut impl FnMut(String, Widget)) {
+    for child in group.children_iter() {
+      let path = format!("{prefix}/{}", child.name());
+
+      match child {
+        Widget::Group(child) => walk(&child, &path, f),
+        child => f(path, child),
+      }
+    }
+  }
+
+  walk(group, &format!("/{}", group.name()), f);
+}
+
+/// Mark all leaf widgets below `root` as unchanged, so only widgets set afterwards are written
+///
+/// Drivers build the tree by setting the values, which leaves the changed flags set.
+pub(crate) fn clear_changed(root: &GroupWidget) {
+  for_each_leaf(root, &mut |_, widget| widget.set_changed(false));
+}
+
+/// Collect all leaf widgets below `root` as [`ConfigEntry`]s
+pub(crate) fn list_entries(root: &GroupWidget) -> Vec<ConfigEntry> {
+  let mut entries = Vec::new();
+  for_each_leaf(root, &mut |path, widget| entries.push(ConfigEntry::new(path, &widget)));
+  entries
+}
+
+/// Error for a single key of [`Camera::apply_config`](crate::Camera::apply_config)
+#[derive(Debug)]
+pub struct ConfigKeyError {
+  /// Key as passed to [`Camera::apply_config`](crate::Camera::apply_config)
+  pub key: String,
+  /// Reason why the key could not be set
+  pub error: Error,
+}
+
+impl fmt::Display for ConfigKeyError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.key, self.error)
+  }
+}
+
+impl std::error::Error for ConfigKeyError {}
+
+/// Find a widget below `root` by name or, if the key contains a `/`, by path
+pub(crate) fn resolve_key(root: &GroupWidget, key: &str) -> Result<Widget> {
+  if key.contains('/') {
+    root.get_child_by_path(key)
+  } else {
+    root.get_child_by_name(key)
+  }
+}
+
+/// Parse and set all values on the tree below `root`, returns the keys that failed
+pub(crate) fn set_values(root: &GroupWidget, values: &[(String, String)]) -> Vec<ConfigKeyError> {
+  values
+    .iter()
+    .filter_map(|(key, value)| {
+      resolve_key(root, key)
+        .and_then(|widget| widget.set_from_str(value))
+        .err()
+        .map(|error| ConfigKeyError { key: key.clone(), error })
+    })
+    .collect()
+}
+
+/// Values of all writable settings of a camera, keyed by their full path
+///
+/// Created by [`Camera::config_snapshot`](crate::Camera::config_snapshot) and applied with
+/// [`Camera::restore_snapshot`](crate::Camera::restore_snapshot). With the `serde` feature
+/// the snapshot can be stored, e.g. as JSON, to restore a known setup later.
+///
+/// Read-only widgets, buttons and dates are not part of the snapshot, restoring
+/// the date of the camera clock is rarely intended.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigSnapshot {
+  values: BTreeMap<String, WidgetValue>,
+}
+
+impl ConfigSnapshot {
+  pub(crate) fn from_root(root: &GroupWidget) -> Self {
+    let mut values = BTreeMap::new();
+
+    for_each_leaf(root, &mut |path, widget| {
+      if widget.readonly() || matches!(widget, Widget::Button(_) | Widget::Date(_)) {
+        return;
+      }
+
+      if let Ok(value) = widget.value() {
+        values.insert(path, value);
+      }
+    });
+
+    Self { values }
+  }
+
+  /// Get the values of the snapshot, keyed by full path
+  pub fn values(&self) -> &BTreeMap<String, WidgetValue> {
+    &self.values
+  }
+
+  /// Get the value of a setting by its full path
+  pub fn get(&self, path: &str) -> Option<&WidgetValue> {
+    self.values.get(path)
+  }
+
+  /// Set the value of a setting by its full path, e.g. to build a partial snapshot
+  pub fn insert(&mut self, path: impl Into<String>, value: WidgetValue) -> Option<WidgetValue> {
+    self.values.insert(path.into(), value)
+  }
+
+  /// Remove a setting from the snapshot
+  pub fn remove(&mut self, path: &str) -> Option<WidgetValue> {
+    self.values.remove(path)
+  }
+
+  /// Number of settings in the snapshot
+  pub fn len(&self) -> usize {
+    self.values.len()
+  }
+
+  /// If true, the snapshot contains no settings
+  pub fn is_empty(&self) -> bool {
+    self.values.is_empty()
+  }
+
+  /// Compare this snapshot with a newer one, see [`ConfigDiff`]
+  pub fn diff(&self, new: &ConfigSnapshot) -> ConfigDiff {
+    ConfigDiff::new(self, new)
+  }
+
+  /// Set the values of the snapshot which differ from the tree below `root`,
+  /// returns the number of changed widgets and the keys that failed
+  pub(crate) fn apply(&self, root: &GroupWidget) -> (usize, Vec<ConfigKeyError>) {
+    let mut changed = 0;
+    let mut errors = Vec::new();
+
+    for (path, value) in &self.values {
+      let result = root.get_child_by_path(path).and_then(|widget| {
+        if widget.value().ok().as_ref() == Some(value) {
+          return Ok(());
+        }
+
+        widget.set_value(value.clone())?;
+        changed += 1;
+        Ok(())
+      });
+
+      if let Err(error) = result {
+        errors.push(ConfigKeyError { key: path.clone(), error });
+      }
+    }
+
+    (changed, errors)
+  }
+}
+
+/// Change of a single setting between two [`ConfigSnapshot`]s
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConfigChange {
+  /// Setting only exists in the new snapshot
+  Added(WidgetValue),
+  /// Setting only exists in the old snapshot
+  Removed(WidgetValue),
+  /// Setting has a different value
+  Changed {
+    /// Value in the old snapshot
+    old: WidgetValue,
+    /// Value in the new snapshot
+    new: WidgetValue,
+  },
+}
+
+/// Differences between two [`ConfigSnapshot`]s, keyed by full path
+///
+/// Created by [`ConfigSnapshot::diff`] or [`Camera::config_diff`](crate::Camera::config_diff).
+/// The [`Display`](fmt::Display) implementation prints one line per change, prefixed with
+/// `+` for added, `-` for removed and `~` for changed settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConfigDiff {
+  changes: BTreeMap<String, ConfigChange>,
+}
+
+impl ConfigDiff {
+  /// Compare two snapshots
+  pub fn new(old: &ConfigSnapshot, new: &ConfigSnapshot) -> Self {
+    let mut changes = BTreeMap::new();
+
+    for (path, old_value) in &old.values {
+      match new.values.get(path) {
+        None => {
+          changes.insert(path.clone(), ConfigChange::Removed(old_value.clone()));
+        }
+        Some(new_value) if new_value != old_value => {
+          changes.insert(
+            path.clone(),
+            ConfigChange::Changed { old: old_value.clone(), new: new_value.clone() },
+          );
+        }
+        Some(_) => {}
+      }
+    }
+
+    for (path, new_value) in &new.values {
+      if !old.values.contains_key(path) {
+        changes.insert(path.clone(), ConfigChange::Added(new_value.clone()));
+      }
+    }
+
+    Self { changes }
+  }
+
+  /// Get the changes, keyed by full path
+  pub fn changes(&self) -> &BTreeMap<String, ConfigChange> {
+    &self.changes
+  }
+
+  /// Number of changed settings
+  pub fn len(&self) -> usize {
+    self.changes.len()
+  }
+
+  /// If true, the snapshots are equal
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+
+  /// Snapshot of the added and changed settings with their new values
+  ///
+  /// Restoring it on a camera in the old state only sets the settings that differ.
+  pub fn to_snapshot(&self) -> ConfigSnapshot {
+    let values = self
+      .changes
+      .iter()
+      .filter_map(|(path, change)| match change {
+        ConfigChange::Added(new) | ConfigChange::Changed { new, .. } => {
+          Some((path.clone(), new.clone()))
+        }
+        ConfigChange::Removed(_) => None,
+      })
+      .collect();
+
+    ConfigSnapshot { values }
+  }
+}
+
+impl fmt::Display for ConfigDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (path, change) in &self.changes {
+      match change {
+        ConfigChange::Added(new) => writeln!(f, "+ {path}: {new:?}")?,
+        ConfigChange::Removed(old) => writeln!(f, "- {path}: {old:?}")?,
+        ConfigChange::Changed { old, new } => writeln!(f, "~ {path}: {old:?} -> {new:?}")?,
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Batch of configuration changes written with a single call to the camera
+///
+/// Created by [`Camera::transaction`], which fetches the full configuration once.
+/// Changes are only made to the local copy until [`commit`](Self::commit), which writes
+/// only the changed widgets in one `gp_camera_set_config` round trip. This is much faster
+/// than one [`Camera::set_config`](crate::Camera::set_config) per widget on many PTP drivers.
+///
+/// Dropping the transaction without committing discards the changes.
+pub struct ConfigTransaction {
+  camera: Camera,
+  root: GroupWidget,
+}
+
+impl ConfigTransaction {
+  pub(crate) fn new(camera: Camera, root: GroupWidget) -> Self {
+    Self { camera, root }
+  }
+
+  /// Root of the local configuration copy
+  pub fn root(&self) -> &GroupWidget {
+    &self.root
+  }
+
+  /// Get a widget of the local copy by name or full path
+  ///
+  /// Values set on the returned widget are part of the transaction.
+  pub fn widget<T: TryFrom<Widget>>(&self, key: &str) -> Result<T>
+  where
+    Error: From<T::Error>,
+  {
+    Ok(resolve_key(&self.root, key)?.try_into()?)
+  }
+
+  /// Set the value of a widget by name or full path, see [`Widget::set_value`]
+  pub fn set(&self, key: &str, value: WidgetValue) -> Result<()> {
+    resolve_key(&self.root, key)?.set_value(value)
+  }
+
+  /// Set the value of a widget by name or full path from a string, see [`Widget::set_from_str`]
+  pub fn set_str(&self, key: &str, value: &str) -> Result<()> {
+    resolve_key(&self.root, key)?.set_from_str(value)
+  }
+
+  /// Full paths of the widgets changed in this transaction
+  pub fn changed_paths(&self) -> Vec<String> {
+    let mut paths = Vec::new();
+    for_each_leaf(&self.root, &mut |path, widget| {
+      if widget.changed() {
+        paths.push(path);
+      }
+    });
+    paths
+  }
+
+  /// Write the changed widgets to the camera
+  pub fn commit(self) -> Task<Result<()>> {
+    self.camera.set_all_config(&self.root)
+  }
+}
+
+/// Cached camera configuration, invalidated by camera events
+///
+/// Fetching the full configuration takes a second or more on many cameras. This wrapper keeps
+/// the last fetched tree and, when [`handle_event`](Self::handle_event) receives a property
+/// change event, only fetches the changed setting again on the next access.
+///
+/// Drivers which report property changes without a name (older libgphoto2 versions)
+/// invalidate the whole cache instead.
+pub struct CachedConfig {
+  camera: Camera,
+  root: GroupWidget,
+  refreshed: HashMap<String, Widget>,
+  stale: HashSet<String>,
+  all_stale: bool,
+}
+
+impl CachedConfig {
+  /// Fetch the full configuration of the camera
+  pub fn new(camera: &Camera) -> Result<Self> {
+    Ok(Self {
+      camera: camera.clone(),
+      root: camera.config().wait()?,
+      refreshed: HashMap::new(),
+      stale: HashSet::new(),
+      all_stale: false,
+    })
+  }
+
+  /// Get a widget by name or full path, fetching it from the camera only if it is stale
+  ///
+  /// Values set on the returned widget are not written to the camera,
+  /// use [`Camera::set_config`](crate::Camera::set_config) for that.
+  pub fn get<T: TryFrom<Widget>>(&mut self, key: &str) -> Result<T>
+  where
+    Error: From<T::Error>,
+  {
+    if self.all_stale {
+      self.refresh()?;
+    }
+
+    let name = key.rsplit('/').next().unwrap_or(key);
+
+    if self.stale.remove(name) {
+      let widget = self.camera.config_key::<Widget>(name).wait()?;
+      self.refreshed.insert(name.to_owned(), widget);
+    }
+
+    let widget = match self.refreshed.get(name) {
+      Some(widget) => widget.clone(),
+      None => resolve_key(&self.root, key)?,
+    };
+
+    Ok(widget.try_into()?)
+  }
+
+  /// Update the cache for an event of the camera, returns `true` if a setting became stale
+  pub fn handle_event(&mut self, event: &CameraEvent) -> bool {
+    let CameraEvent::Unknown(description) = event else { return false };
+
+    match parse_property_change(description) {
+      Some(Some(name)) => {
+        self.invalidate(name);
+        true
+      }
+      Some(None) => {
+        self.invalidate_all();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Mark a setting as stale, so it is fetched again on the next access
+  pub fn invalidate(&mut self, name: &str) {
+    self.stale.insert(name.to_owned());
+  }
+
+  /// Mark the whole configuration as stale, so it is fetched again on the next access
+  pub fn invalidate_all(&mut self) {
+    self.all_stale = true;
+  }
+
+  /// Fetch the full configuration again
+  pub fn refresh(&mut self) -> Result<()> {
+    self.root = self.camera.config().wait()?;
+    self.refreshed.clear();
+    self.stale.clear();
+    self.all_stale = false;
+
+    Ok(())
+  }
+}
+
+/// Parse a property change event like `PTP Property d00e changed, "iso" to "400"`
+///
+/// Returns `Some(None)` for property changes without a name, `None` for other events.
+fn parse_property_change(description: &str) -> Option<Option<&str>> {
+  let (_, rest) = description.strip_prefix("PTP Property ")?.split_once(" changed")?;
+
+  Some(rest.strip_prefix(", \"").and_then(|rest| rest.split_once('"')).map(|(name, _)| name))
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  #[test]
+  fn test_apply_config() {
+    let camera = crate::lock_capture_target();
+
+    let errors = camera
+      .apply_config([("capturetarget", "Memory card"), ("/main/settings/missing", "1")])
+      .wait()
+      .unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "/main/settings/missing");
+
+    let capture_target =
+      camera.config_key::<crate::widget::RadioWidget>("capturetarget").wait().unwrap();
+    assert_eq!(capture_target.choice(), "Memory card");
+  }
+
+  #[test]
+  fn test_config_snapshot() {
+    let camera = crate::lock_capture_target();
+
+    camera.apply_config([("capturetarget", "Internal RAM")]).wait().unwrap();
+    let snapshot = camera.config_snapshot().wait().unwrap();
+    assert!(snapshot.get("/main/settings/datetime").is_none());
+
+    camera.apply_config([("capturetarget", "Memory card")]).wait().unwrap();
+    assert!(camera.restore_snapshot(&snapshot).wait().unwrap().is_empty());
+
+    let capture_target =
+      camera.config_key::<crate::widget::RadioWidget>("capturetarget").wait().unwrap();
+    assert_eq!(capture_target.choice(), "Internal RAM");
+  }
+
+  #[test]
+  fn test_config_diff() {
+    use super::ConfigChange;
+    use crate::widget::WidgetValue;
+
+    let camera = crate::lock_capture_target();
+
+    camera.apply_config([("capturetarget", "Internal RAM")]).wait().unwrap();
+    let snapshot = camera.config_snapshot().wait().unwrap();
+    assert!(camera.config_diff(&snapshot).wait().unwrap().is_empty());
+
+    camera.apply_config([("capturetarget", "Memory card")]).wait().unwrap();
+    let diff = camera.config_diff(&snapshot).wait().unwrap();
+
+    assert_eq!(diff.len(), 1);
+    assert_eq!(
+      diff.changes().get("/main/settings/capturetarget"),
+      Some(&ConfigChange::Changed {
+        old: WidgetValue::Choice("Internal RAM".to_owned()),
+        new: WidgetValue::Choice("Memory card".to_owned()),
+      })
+    );
+  }
+
+  #[test]
+  fn test_config_transaction() {
+    let camera = crate::lock_capture_target();
+
+    camera.apply_config([("capturetarget", "Internal RAM")]).wait().unwrap();
+
+    let transaction = camera.transaction().unwrap();
+    assert!(transaction.changed_paths().is_empty());
+
+    transaction.set_str("capturetarget", "Memory card").unwrap();
+    assert_eq!(transaction.changed_paths(), ["/main/settings/capturetarget"]);
+    transaction.commit().wait().unwrap();
+
+    let capture_target =
+      camera.config_key::<crate::widget::RadioWidget>("capturetarget").wait().unwrap();
+    assert_eq!(capture_target.choice(), "Memory card");
+  }
+
+  #[test]
+  fn test_set_config_if_changed() {
+    let camera = crate::lock_capture_target();
+
+    camera.apply_config([("capturetarget", "Internal RAM")]).wait().unwrap();
+    let capture_target =
+      camera.config_key::<crate::widget::RadioWidget>("capturetarget").wait().unwrap();
+
+    assert!(!camera.set_config_if_changed(&capture_target).wait().unwrap());
+
+    capture_target.set_choice("Memory card").unwrap();
+    assert!(camera.set_config_if_changed(&capture_target).wait().unwrap());
+  }
+
+  #[test]
+  fn test_cached_config() {
+    use super::CachedConfig;
+    use crate::{camera::CameraEvent, widget::RadioWidget};
+
+    let camera = crate::lock_capture_target();
+    camera.apply_config([("capturetarget", "Internal RAM")]).wait().unwrap();
+
+    let mut config = CachedConfig::new(&camera).unwrap();
+    camera.apply_config([("capturetarget", "Memory card")]).wait().unwrap();
+    assert_eq!(config.get::<RadioWidget>("capturetarget").unwrap().choice(), "Internal RAM");
+
+    assert!(config.handle_event(&CameraEvent::Unknown(
+      r#"PTP Property d000 changed, "capturetarget" to "Memory card""#.to_owned()
+    )));
+    assert_eq!(config.get::<RadioWidget>("capturetarget").unwrap().choice(), "Memory card");
+  }
+
+  #[test]
+  fn test_list_config() {
+    let entries = crate::sample_camera().list_config().wait().unwrap();
+
+    let f_number =
+      entries.iter().find(|entry| entry.path == "/main/capturesettings/f-number").unwrap();
+    assert_eq!(f_number.name, "f-number");
+    assert_eq!(f_number.widget_type, crate::widget::WidgetType::Radio);
+    assert!(f_number.choices.contains(&"f/2.8".to_owned()));
+
+    assert!(entries.iter().all(|entry| entry.widget_type != crate::widget::WidgetType::Group));
+  }
+}
@@ -12,6 +12,8 @@ use crate::{
 use std::ffi;
 use std::ops::DerefMut;
 use std::os::raw::{c_char, c_float, c_uint, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 /// Progress handler trait
@@ -33,6 +35,115 @@ pub(crate) trait CancelHandler: 'static + Send {
   fn cancel(&mut self) -> bool;
 }
 
+type MessageHandler = Box<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Default)]
+struct MessageHandlers {
+  status: Option<MessageHandler>,
+  error: Option<MessageHandler>,
+  message: Option<MessageHandler>,
+}
+
+/// Builder for a [`Context`]
+///
+/// By default the status, error and message outputs of libgphoto2 are forwarded to the
+/// [`log`](https://docs.rs/log) crate, setting a handler replaces this for the built context.
+///
+/// ## Example
+///
+/// ```no_run
+/// use gphoto2::{Context, Result};
+///
+/// # fn main() -> Result<()> {
+/// let context = Context::builder()
+///   .status_handler(|status| println!("Status: {status}"))
+///   .error_handler(|error| eprintln!("Error: {error}"))
+///   .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ContextBuilder {
+  handlers: MessageHandlers,
+  camlibs_dir: Option<PathBuf>,
+  iolibs_dir: Option<PathBuf>,
+}
+
+impl ContextBuilder {
+  /// Handle status messages, e.g. the name of the file currently being downloaded
+  pub fn status_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+    self.handlers.status = Some(Box::new(handler));
+    self
+  }
+
+  /// Handle human-readable error messages
+  pub fn error_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+    self.handlers.error = Some(Box::new(handler));
+    self
+  }
+
+  /// Handle messages which should be shown to the user
+  pub fn message_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+    self.handlers.message = Some(Box::new(handler));
+    self
+  }
+
+  /// Load camera drivers from a directory instead of the one libgphoto2 was built with
+  ///
+  /// This is useful for bundled applications (AppImage, Flatpak, installers) where the
+  /// drivers are relocated. Only this context and the cameras opened with it use the
+  /// directory, the environment of the process isn't changed.
+  pub fn camlibs_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.camlibs_dir = Some(dir.into());
+    self
+  }
+
+  /// Load port drivers from a directory instead of the one libgphoto2 was built with
+  ///
+  /// See [`ContextBuilder::camlibs_dir`]. libgphoto2 has no API to load port drivers from a
+  /// directory, so the `IOLIBS` environment variable is set for the whole process while the
+  /// driver list is loaded and restored afterwards. Other threads reading `IOLIBS` at the
+  /// same time see the directory.
+  pub fn iolibs_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.iolibs_dir = Some(dir.into());
+    self
+  }
+
+  /// Create the [`Context`]
+  pub fn build(self) -> Result<Context> {
+    let mut context = Context::new()?;
+
+    if self.camlibs_dir.is_some() || self.iolibs_dir.is_some() {
+      context.driver_dirs = Some(Arc::new(DriverDirs {
+        camlibs: self.camlibs_dir.map(|dir| utf8_dir("camlibs", dir)).transpose()?,
+        iolibs: self.iolibs_dir,
+      }));
+    }
+
+    let MessageHandlers { status, error, message } = &self.handlers;
+    if status.is_some() || error.is_some() || message.is_some() {
+      context.set_message_handlers(self.handlers);
+    }
+
+    Ok(context)
+  }
+}
+
+/// Driver directories set with [`ContextBuilder::camlibs_dir`] and [`ContextBuilder::iolibs_dir`]
+struct DriverDirs {
+  camlibs: Option<String>,
+  iolibs: Option<PathBuf>,
+}
+
+fn utf8_dir(name: &str, dir: PathBuf) -> Result<String> {
+  dir.into_os_string().into_string().map_err(|dir| {
+    Error::bad_parameters(format!(
+      "{name} directory {} is not valid UTF-8",
+      Path::new(&dir).display()
+    ))
+  })
+}
+
 /// Context used internally by libgphoto2
 ///
 /// ## Example
@@ -56,12 +167,23 @@ pub struct Context {
   pub(crate) inner: BackgroundPtr<libgphoto2_sys::GPContext>,
   progress_handler: Option<Arc<Mutex<dyn ProgressHandler>>>,
   cancel_handler: Option<Arc<Mutex<dyn CancelHandler>>>,
+  message_handlers: Option<Arc<MessageHandlers>>,
+  driver_dirs: Option<Arc<DriverDirs>>,
 }
 
 impl Drop for Context {
   fn drop(&mut self) {
     let context = self.inner;
 
+    // Make sure libgphoto2 doesn't call into the handlers after they are freed.
+    if matches!(&self.message_handlers, Some(handlers) if Arc::strong_count(handlers) == 1) {
+      unsafe {
+        libgphoto2_sys::gp_context_set_status_func(*context, None, std::ptr::null_mut());
+        libgphoto2_sys::gp_context_set_error_func(*context, None, std::ptr::null_mut());
+        libgphoto2_sys::gp_context_set_message_func(*context, None, std::ptr::null_mut());
+      }
+    }
+
     unsafe {
       Task::new(move || libgphoto2_sys::gp_context_unref(*context));
     }
@@ -78,6 +200,8 @@ impl Clone for Context {
       inner: self.inner,
       progress_handler: self.progress_handler.clone(),
       cancel_handler: self.cancel_handler.clone(),
+      message_handlers: self.message_handlers.clone(),
+      driver_dirs: self.driver_dirs.clone(),
     }
   }
 }
@@ -103,7 +227,7 @@ impl Context {
   /// Create a new context
   pub fn new() -> Result<Self> {
     #[cfg(feature = "extended_logs")]
-    crate::helper::hook_gp_log();
+    crate::logging::install_default();
 
     let context_ptr = unsafe { libgphoto2_sys::gp_context_new() };
 
@@ -114,7 +238,18 @@ impl Context {
     #[cfg(not(feature = "extended_logs"))]
     crate::helper::hook_gp_context_log_func(context_ptr);
 
-    Ok(Self { inner: BackgroundPtr(context_ptr), progress_handler: None, cancel_handler: None })
+    Ok(Self {
+      inner: BackgroundPtr(context_ptr),
+      progress_handler: None,
+      cancel_handler: None,
+      message_handlers: None,
+      driver_dirs: None,
+    })
+  }
+
+  /// Create a [`ContextBuilder`] to configure the context before creating it
+  pub fn builder() -> ContextBuilder {
+    ContextBuilder::default()
   }
 
   /// Lists all available cameras and their ports
@@ -122,17 +257,46 @@ impl Context {
   /// Returns a list of (camera_name, port_path)
   /// which can be used in [`Context::get_camera`].
   pub fn list_cameras(&self) -> Task<Result<CameraListIter>> {
-    let context = self.clone().inner;
+    let context = self.clone();
 
-    unsafe {
-      Task::new(move || {
-        let camera_list = CameraList::new()?;
-        try_gp_internal!(gp_camera_autodetect(*camera_list.inner, *context)?);
+    unsafe { Task::new(move || Ok(CameraListIter::new(context.detect_cameras()?))) }
+      .context(self.inner)
+  }
 
-        Ok(CameraListIter::new(camera_list))
-      })
-    }
-    .context(self.inner)
+  /// Load the list of all camera models supported by libgphoto2
+  pub fn abilities_list(&self) -> Task<Result<AbilitiesList>> {
+    let context = self.clone();
+
+    unsafe { Task::new(move || AbilitiesList::new_inner(&context)) }.context(self.inner)
+  }
+
+  /// Connect to a network camera over PTP/IP
+  ///
+  /// `addr` is the IP address of the camera, optionally followed by a port (`host:port`).
+  /// If no port is given, libgphoto2 uses the default PTP/IP port 15740.
+  /// The model name must match one of the models known to libgphoto2, see [`Context::abilities_list`].
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let camera = context.connect_ptp_ip("Canon EOS (WLAN)", "192.168.1.10").wait()?;
+  ///
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn connect_ptp_ip(&self, model: &str, addr: &str) -> Task<Result<Camera>> {
+    let addr = addr.strip_prefix("ptpip:").unwrap_or(addr);
+
+    self.get_camera(&CameraDescriptor { model: model.to_owned(), port: format!("ptpip:{addr}") })
+  }
+
+  /// Load the list of all ports available to libgphoto2
+  pub fn list_ports(&self) -> Task<Result<PortInfoList>> {
+    let context = self.clone();
+
+    unsafe { Task::new(move || PortInfoList::new_inner(&context)) }.context(self.inner)
   }
 
   /// Auto chooses a camera
@@ -153,15 +317,48 @@ impl Context {
   pub fn autodetect_camera(&self) -> Task<Result<Camera>> {
     let context = self.clone();
 
+    unsafe { Task::new(move || context.autodetect_inner()) }.context(self.inner)
+  }
+
+  /// Connect to the virtual camera of libgphoto2, serving the files of `dir`
+  ///
+  /// The virtual camera is a PTP camera on a virtual USB port, which exposes the files in `dir`
+  /// as its storage. Captured images are taken from the files in `dir` as well. This allows
+  /// writing integration tests which run without any camera connected.
+  ///
+  /// Requires the `test-utils` feature, which builds a stripped down libgphoto2 containing
+  /// only the virtual camera, so it must not be enabled outside of tests.
+  ///
+  /// The directory is read by libgphoto2 from the `VCAMERADIR` environment variable when
+  /// connecting, so the variable is set for the whole process while the camera connects and
+  /// restored afterwards. Changing the directory only affects cameras created afterwards.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let camera = context.virtual_camera("tests/camera")?;
+  ///
+  /// for folder in camera.fs().list_folders("/").wait()? {
+  ///   println!("{}", folder);
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  #[cfg(feature = "test-utils")]
+  pub fn virtual_camera(&self, dir: impl AsRef<Path>) -> Result<Camera> {
+    let context = self.clone();
+    let dir = dir.as_ref().to_owned();
+
+    // The virtual camera only reads its directory from the environment
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_camera_new(&out camera_ptr)?);
-        try_gp_internal!(gp_camera_init(camera_ptr, *context.inner)?);
-
-        Ok(Camera::new(BackgroundPtr(camera_ptr), context))
+        crate::helper::with_libgphoto2_env("VCAMERADIR", &dir, || context.autodetect_inner())
       })
-      .context(self.inner)
     }
+    .context(self.inner)
+    .wait()
   }
 
   /// Initialize a camera knowing its model name and port path
@@ -181,36 +378,7 @@ impl Context {
     let context = self.clone();
     let camera_descriptor = camera_descriptor.clone();
 
-    unsafe {
-      Task::new(move || {
-        let abilities_list = AbilitiesList::new_inner(&context)?;
-        let port_info_list = PortInfoList::new_inner()?;
-
-        try_gp_internal!(gp_camera_new(&out camera)?);
-
-        try_gp_internal!(let model_index = gp_abilities_list_lookup_model(
-          *abilities_list.inner,
-          to_c_string!(camera_descriptor.model.as_str())
-        )?);
-
-        try_gp_internal!(gp_abilities_list_get_abilities(
-          *abilities_list.inner,
-          model_index,
-          &out model_abilities
-        )?);
-        try_gp_internal!(gp_camera_set_abilities(camera, model_abilities)?);
-
-        try_gp_internal!(let p = gp_port_info_list_lookup_path(
-          port_info_list.inner,
-          to_c_string!(camera_descriptor.port.as_str())
-        )?);
-        let port_info = port_info_list.get_port_info(p)?;
-        try_gp_internal!(gp_camera_set_port_info(camera, port_info.inner)?);
-
-        Ok(Camera::new(BackgroundPtr(camera), context))
-      })
-    }
-    .context(self.inner)
+    unsafe { Task::new(move || context.open_camera(&camera_descriptor)) }.context(self.inner)
   }
 
   /// Set context progress functions
@@ -263,6 +431,56 @@ impl Context {
     self.progress_handler = Some(progress_handler);
   }
 
+  fn set_message_handlers(&mut self, handlers: MessageHandlers) {
+    unsafe extern "C" fn message_func(
+      _ctx: *mut libgphoto2_sys::GPContext,
+      message: *const c_char,
+      data: *mut c_void,
+    ) {
+      let handler = &*data.cast::<MessageHandler>();
+      let message = chars_to_string(message);
+
+      if catch_unwind(AssertUnwindSafe(|| handler(&message))).is_err() {
+        log::error!("Context message handler panicked while handling: {}", message);
+      }
+    }
+
+    fn handler_ptr(handler: &MessageHandler) -> *mut c_void {
+      let ptr: *const MessageHandler = handler;
+      ptr.cast_mut().cast()
+    }
+
+    let handlers = Arc::new(handlers);
+
+    unsafe {
+      if let Some(status) = &handlers.status {
+        libgphoto2_sys::gp_context_set_status_func(
+          *self.inner,
+          Some(message_func),
+          handler_ptr(status),
+        );
+      }
+
+      if let Some(error) = &handlers.error {
+        libgphoto2_sys::gp_context_set_error_func(
+          *self.inner,
+          Some(message_func),
+          handler_ptr(error),
+        );
+      }
+
+      if let Some(message) = &handlers.message {
+        libgphoto2_sys::gp_context_set_message_func(
+          *self.inner,
+          Some(message_func),
+          handler_ptr(message),
+        );
+      }
+    }
+
+    self.message_handlers = Some(handlers);
+  }
+
   pub(crate) fn set_cancel_handler<H>(&mut self, handler: H)
   where
     H: CancelHandler,
@@ -318,7 +536,100 @@ impl Context {
 
 impl Context {
   pub(crate) fn from_ptr(ptr: BackgroundPtr<libgphoto2_sys::GPContext>) -> Self {
-    Self { cancel_handler: None, inner: ptr, progress_handler: None }
+    Self {
+      cancel_handler: None,
+      inner: ptr,
+      progress_handler: None,
+      message_handlers: None,
+      driver_dirs: None,
+    }
+  }
+
+  /// Directory of the camera drivers set with [`ContextBuilder::camlibs_dir`]
+  pub(crate) fn camlibs_dir(&self) -> Option<&str> {
+    self.driver_dirs.as_ref()?.camlibs.as_deref()
+  }
+
+  /// Directory of the port drivers set with [`ContextBuilder::iolibs_dir`]
+  pub(crate) fn iolibs_dir(&self) -> Option<&Path> {
+    self.driver_dirs.as_ref()?.iolibs.as_deref()
+  }
+
+  /// Detect the connected cameras, must be called from a [`Task`]
+  fn detect_cameras(&self) -> Result<CameraList> {
+    let camera_list = CameraList::new()?;
+
+    if self.driver_dirs.is_none() {
+      try_gp_internal!(gp_camera_autodetect(*camera_list.inner, *self.inner)?);
+      return Ok(camera_list);
+    }
+
+    // gp_camera_autodetect always loads the default drivers
+    let abilities_list = AbilitiesList::new_inner(self)?;
+    let port_info_list = PortInfoList::new_inner(self)?;
+    let detected = CameraList::new()?;
+
+    try_gp_internal!(gp_abilities_list_detect(
+      *abilities_list.inner,
+      *port_info_list.inner,
+      *detected.inner,
+      *self.inner
+    )?);
+
+    // Like gp_camera_autodetect, skip the generic USB port
+    for descriptor in CameraListIter::new(detected).filter(|descriptor| descriptor.port != "usb:") {
+      try_gp_internal!(gp_list_append(
+        *camera_list.inner,
+        to_c_string!(descriptor.model.as_str()),
+        to_c_string!(descriptor.port.as_str())
+      )?);
+    }
+
+    Ok(camera_list)
+  }
+
+  /// Connect to the first detected camera, must be called from a [`Task`]
+  fn autodetect_inner(self) -> Result<Camera> {
+    if self.driver_dirs.is_some() {
+      let descriptor = CameraListIter::new(self.detect_cameras()?).next().ok_or_else(|| {
+        Error::new(libgphoto2_sys::GP_ERROR_MODEL_NOT_FOUND, Some("No camera detected".to_owned()))
+      })?;
+
+      return self.open_camera(&descriptor);
+    }
+
+    try_gp_internal!(gp_camera_new(&out camera_ptr)?);
+    try_gp_internal!(gp_camera_init(camera_ptr, *self.inner)?);
+
+    Ok(Camera::new(BackgroundPtr(camera_ptr), self))
+  }
+
+  /// Connect to a camera by its model and port, must be called from a [`Task`]
+  fn open_camera(self, camera_descriptor: &CameraDescriptor) -> Result<Camera> {
+    let abilities_list = AbilitiesList::new_inner(&self)?;
+    let port_info_list = PortInfoList::new_inner(&self)?;
+
+    try_gp_internal!(gp_camera_new(&out camera)?);
+
+    let init_camera = || -> Result<()> {
+      let model_abilities = abilities_list.find_model(&camera_descriptor.model)?;
+      try_gp_internal!(gp_camera_set_abilities(camera, *model_abilities.inner)?);
+
+      let port_info = port_info_list.find_path(&camera_descriptor.port)?;
+      try_gp_internal!(gp_camera_set_port_info(camera, port_info.inner)?);
+
+      // Initialize now, so connection errors are reported here instead of on first use.
+      try_gp_internal!(gp_camera_init(camera, *self.inner)?);
+
+      Ok(())
+    };
+
+    if let Err(error) = init_camera() {
+      unsafe { libgphoto2_sys::gp_camera_unref(camera) };
+      return Err(error);
+    }
+
+    Ok(Camera::new(BackgroundPtr(camera), self))
   }
 }
 
@@ -408,4 +719,62 @@ mod tests {
 
     let _ = task.wait();
   }
+
+  #[test]
+  fn test_driver_dirs() {
+    use crate::list::CameraDescriptor;
+    use std::path::PathBuf;
+
+    // Sets up the virtual camera
+    crate::sample_context();
+
+    fn version_dir(library: &str) -> PathBuf {
+      let dir = libgphoto2_sys::test_utils::libgphoto2_dir().join("lib").join(library);
+
+      std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap()
+    }
+
+    let context = super::Context::builder()
+      .camlibs_dir(version_dir("libgphoto2"))
+      .iolibs_dir(version_dir("libgphoto2_port"))
+      .build()
+      .unwrap();
+
+    let cameras = context.list_cameras().wait().unwrap().collect::<Vec<_>>();
+    assert_eq!(
+      cameras,
+      [CameraDescriptor { model: "Nikon DSC D750".to_owned(), port: "usb:001,001".to_owned() }]
+    );
+
+    let camera = context.autodetect_camera().wait().unwrap();
+    assert_eq!(camera.abilities().model(), "Nikon DSC D750");
+
+    // A directory without drivers
+    let context = super::Context::builder()
+      .camlibs_dir(libgphoto2_sys::test_utils::vcamera_dir())
+      .build()
+      .unwrap();
+    assert!(context.autodetect_camera().wait().is_err());
+  }
+
+  #[cfg(feature = "test-utils")]
+  #[test]
+  fn test_virtual_camera() {
+    use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+
+    // A directory other than the default VCAMERADIR, which contains blank.jpg
+    let camera_dir = tempfile::tempdir().unwrap();
+    std::fs::write(camera_dir.path().join("virtual.jpg"), SAMPLE_IMAGE).unwrap();
+
+    let camera = crate::sample_context().virtual_camera(camera_dir.path()).unwrap();
+    assert_eq!(camera.abilities().model(), "Nikon DSC D750");
+
+    let files: Vec<_> = camera.fs().list_files("/store_00010001").wait().unwrap().collect();
+    assert!(files.iter().any(|name| name == "virtual.jpg"));
+    assert!(!files.iter().any(|name| name == "blank.jpg"));
+  }
 }
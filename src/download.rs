@@ -0,0 +1,246 @@
+//! Downloading files from several cameras in the background
+//!
+//! A [`DownloadQueue`] has a worker thread for every camera, which downloads the queued files
+//! of its camera to the host in the order they were queued and reports every completed download
+//! to a callback.
+//!
+//! The queue moves downloads off the thread receiving the files and bounds the number of pending
+//! files, it does not make downloads faster: all libgphoto2 calls run on a single background
+//! thread, so only one file is transferred at a time. The workers queue their next file as soon
+//! as the previous one finished, so downloads of different cameras take turns file by file
+//! and the total time is the same as downloading all files one after another.
+//!
+//! ## Downloading the captures of all cameras
+//! ```no_run
+//! use gphoto2::{camera::CameraEvent, download::DownloadQueue, pool::CameraPool, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let pool = CameraPool::open_all(&context, Duration::from_millis(50))?;
+//!
+//! let queue = DownloadQueue::new(pool.cameras(), 16, |download| match download.result {
+//!   Ok(path) => println!("Camera {} saved {}", download.camera, path.display()),
+//!   Err(error) => eprintln!("Camera {} failed to download {}: {}", download.camera, download.camera_path, error),
+//! })?;
+//!
+//! for pool_event in pool.events() {
+//!   if let Ok(CameraEvent::NewFile(file)) = pool_event.event {
+//!     queue.push(pool_event.index, file, format!("ingest/camera-{}/", pool_event.index))?;
+//!   }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{file::CameraFilePath, Camera, Error, Result};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+  path::PathBuf,
+  sync::Arc,
+  thread::{self, JoinHandle},
+};
+
+/// Download completed by a [`DownloadQueue`]
+#[derive(Debug)]
+pub struct CompletedDownload {
+  /// Index of the camera in the queue
+  pub camera: usize,
+  /// Path of the file on the camera
+  pub camera_path: CameraFilePath,
+  /// Path the file was saved to, or the error that occurred while downloading it
+  pub result: Result<PathBuf>,
+}
+
+struct Job {
+  camera_path: CameraFilePath,
+  dest: PathBuf,
+}
+
+struct Worker {
+  jobs: Option<Sender<Job>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+/// Queue downloading files of several cameras to the host in the background
+///
+/// Files of the same camera are downloaded one after another in the order they were pushed,
+/// files of different cameras take turns on the libgphoto2 thread (see the [module](self)
+/// documentation).
+///
+/// Every camera has a bounded queue of pending files, [`DownloadQueue::push`] blocks while
+/// the queue of the camera is full, so memory use stays bounded even if the cameras produce
+/// files faster than they can be downloaded.
+///
+/// Dropping the queue or calling [`DownloadQueue::shutdown`] waits until all queued files
+/// were downloaded.
+pub struct DownloadQueue {
+  workers: Vec<Worker>,
+}
+
+impl DownloadQueue {
+  /// Start a worker for every camera, the index of a camera in `cameras` identifies it
+  ///
+  /// Each camera can have up to `capacity` pending files. `on_complete` is called from the
+  /// worker threads after every download, including failed ones. For a single camera
+  /// it is called in the order the files were pushed, calls for different cameras can run
+  /// at the same time.
+  pub fn new(
+    cameras: &[Camera],
+    capacity: usize,
+    on_complete: impl Fn(CompletedDownload) + Send + Sync + 'static,
+  ) -> Result<Self> {
+    let on_complete: Arc<dyn Fn(CompletedDownload) + Send + Sync> = Arc::new(on_complete);
+    let mut workers = Vec::with_capacity(cameras.len());
+
+    for (index, camera) in cameras.iter().enumerate() {
+      let (jobs, job_receiver) = crossbeam_channel::bounded(capacity);
+      let (camera, on_complete) = (camera.clone(), on_complete.clone());
+
+      let thread = thread::Builder::new()
+        .name(format!("gphoto2-download-{index}"))
+        .spawn(move || download_worker(index, camera, job_receiver, on_complete))?;
+
+      workers.push(Worker { jobs: Some(jobs), thread: Some(thread) });
+    }
+
+    Ok(Self { workers })
+  }
+
+  /// Queue a file of the camera at `camera` to be downloaded to `dest`
+  ///
+  /// `dest` is handled like in [`CameraFilePath::download_to`]. Blocks while the queue
+  /// of the camera is full.
+  pub fn push(
+    &self,
+    camera: usize,
+    camera_path: CameraFilePath,
+    dest: impl Into<PathBuf>,
+  ) -> Result<()> {
+    let jobs =
+      self.workers.get(camera).and_then(|worker| worker.jobs.as_ref()).ok_or_else(|| {
+        Error::bad_parameters(format!("No camera with index {camera} in the queue"))
+      })?;
+
+    jobs
+      .send(Job { camera_path, dest: dest.into() })
+      .map_err(|_| Error::new(libgphoto2_sys::GP_ERROR_IO, Some("Download worker stopped".into())))
+  }
+
+  /// Number of files waiting to be downloaded, excluding the files being downloaded
+  pub fn pending(&self) -> usize {
+    self.workers.iter().filter_map(|worker| worker.jobs.as_ref()).map(Sender::len).sum()
+  }
+
+  /// Stop accepting files and wait until all queued files were downloaded
+  ///
+  /// `on_complete` was called for every queued file when this returns.
+  pub fn shutdown(mut self) {
+    self.stop();
+  }
+
+  fn stop(&mut self) {
+    // Disconnecting the channels stops the workers once their queues are empty
+    for worker in &mut self.workers {
+      worker.jobs.take();
+    }
+
+    for worker in &mut self.workers {
+      if let Some(thread) = worker.thread.take() {
+        thread.join().ok();
+      }
+    }
+  }
+}
+
+impl Drop for DownloadQueue {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+fn download_worker(
+  index: usize,
+  camera: Camera,
+  jobs: Receiver<Job>,
+  on_complete: Arc<dyn Fn(CompletedDownload) + Send + Sync>,
+) {
+  for Job { camera_path, dest } in jobs {
+    let result = camera_path.download_to(&camera, dest);
+
+    on_complete(CompletedDownload { camera: index, camera_path, result });
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::DownloadQueue;
+  use crate::{file::CameraFilePath, sample_camera};
+  use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn test_download_queue() {
+    let dir = tempfile::tempdir().unwrap();
+    let completed = Arc::new(Mutex::new(Vec::new()));
+
+    let queue = {
+      let completed = completed.clone();
+      DownloadQueue::new(&[sample_camera()], 1, move |download| {
+        completed.lock().unwrap().push(download)
+      })
+      .unwrap()
+    };
+
+    let folders = ["/store_00010001/foo", "/store_00010001/bar", "/store_00010001/missing"];
+    for (index, folder) in folders.into_iter().enumerate() {
+      let file = CameraFilePath::new(folder, "blank.jpg").unwrap();
+      queue.push(0, file, dir.path().join(format!("{index}/"))).unwrap();
+    }
+
+    let file = CameraFilePath::new(folders[0], "blank.jpg").unwrap();
+    assert!(queue.push(1, file, dir.path()).is_err());
+
+    queue.shutdown();
+
+    let completed = completed.lock().unwrap();
+    assert_eq!(completed.len(), folders.len());
+
+    for (index, download) in completed.iter().enumerate() {
+      assert_eq!(download.camera, 0);
+      assert_eq!(download.camera_path.folder(), folders[index]);
+    }
+
+    for (index, download) in completed[..2].iter().enumerate() {
+      let path = download.result.as_ref().unwrap();
+      assert_eq!(path, &dir.path().join(format!("{index}/blank.jpg")));
+      assert_eq!(std::fs::read(path).unwrap(), SAMPLE_IMAGE);
+    }
+
+    assert!(completed[2].result.is_err());
+  }
+
+  #[test]
+  fn test_download_queue_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let completed = Arc::new(Mutex::new(0));
+
+    let queue = {
+      let completed = completed.clone();
+      DownloadQueue::new(&[sample_camera()], 4, move |download| {
+        download.result.unwrap();
+        *completed.lock().unwrap() += 1;
+      })
+      .unwrap()
+    };
+
+    for index in 0..3 {
+      let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+      queue.push(0, file, dir.path().join(format!("{index}/"))).unwrap();
+    }
+
+    drop(queue);
+
+    assert_eq!(*completed.lock().unwrap(), 3);
+  }
+}
@@ -1,7 +1,7 @@
 //! Error handling
 
 use crate::helper::chars_to_string;
-use std::{error, fmt, os::raw::c_int};
+use std::{cell::RefCell, error, fmt, os::raw::c_int, sync::Arc};
 
 #[cfg(feature = "serde")]
 use serde::Serialize;
@@ -10,7 +10,7 @@ use serde::Serialize;
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Error type
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum ErrorKind {
   /// GP_ERROR or something else
   Other,
@@ -66,6 +66,21 @@ pub enum ErrorKind {
   UnknownPort,
   /// Couldn't claim USB device.
   IoUsbClaim,
+  /// The operation was cancelled
+  Cancelled,
+}
+
+thread_local! {
+  /// Camera the libgphoto2 calls on the current thread are made for
+  static CURRENT_CAMERA: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with errors of libgphoto2 calls being attributed to `camera`
+pub(crate) fn with_camera<T>(camera: Option<Arc<str>>, f: impl FnOnce() -> T) -> T {
+  let previous = CURRENT_CAMERA.replace(camera);
+  let result = f();
+  CURRENT_CAMERA.set(previous);
+  result
 }
 
 /// General error
@@ -73,12 +88,24 @@ pub enum ErrorKind {
 pub struct Error {
   error: c_int,
   info: Option<String>,
+  function: Option<&'static str>,
+  camera: Option<Arc<str>>,
 }
 
 impl Error {
   /// Creates a new error from a gphoto internal error
   pub fn new(error: c_int, info: Option<String>) -> Self {
-    Self { error, info }
+    Self { error, info, function: None, camera: None }
+  }
+
+  /// Creates a [`BadParameters`](ErrorKind::BadParameters) error with a description
+  pub(crate) fn bad_parameters(info: impl Into<String>) -> Self {
+    Self::new(libgphoto2_sys::GP_ERROR_BAD_PARAMETERS, Some(info.into()))
+  }
+
+  /// Creates a [`NotSupported`](ErrorKind::NotSupported) error with a description
+  pub(crate) fn not_supported(info: impl Into<String>) -> Self {
+    Self::new(libgphoto2_sys::GP_ERROR_NOT_SUPPORTED, Some(info.into()))
   }
 
   /// Checks the status code and creates a new error if non-zero.
@@ -90,6 +117,82 @@ impl Error {
     }
   }
 
+  /// Checks the status code of a libgphoto2 function and creates a new error
+  /// recording the function and the current camera if non-zero.
+  pub(crate) fn check_call(status: c_int, function: &'static str) -> Result<c_int> {
+    Self::check(status).map_err(|mut error| {
+      error.function = Some(function);
+      error.camera = CURRENT_CAMERA.with_borrow(Clone::clone);
+      error
+    })
+  }
+
+  /// Raw libgphoto2 error code, e.g. [`GP_ERROR_TIMEOUT`](libgphoto2_sys::GP_ERROR_TIMEOUT)
+  pub fn code(&self) -> c_int {
+    self.error
+  }
+
+  /// Additional description of the error
+  pub fn info(&self) -> Option<&str> {
+    self.info.as_deref()
+  }
+
+  /// Name of the libgphoto2 function which failed, e.g. `gp_camera_capture`
+  pub fn function(&self) -> Option<&str> {
+    self.function
+  }
+
+  /// Camera the failed operation was made for, as `<model> at <port>`
+  pub fn camera(&self) -> Option<&str> {
+    self.camera.as_deref()
+  }
+
+  /// The operation timed out
+  pub fn is_timeout(&self) -> bool {
+    self.kind() == ErrorKind::Timeout
+  }
+
+  /// The camera or driver doesn't support the operation
+  pub fn is_not_supported(&self) -> bool {
+    self.kind() == ErrorKind::NotSupported
+  }
+
+  /// The camera is busy, e.g. while writing to the card, retrying later may succeed
+  pub fn is_busy(&self) -> bool {
+    self.kind() == ErrorKind::CameraBusy
+  }
+
+  /// The operation was cancelled
+  pub fn is_cancelled(&self) -> bool {
+    self.kind() == ErrorKind::Cancelled
+  }
+
+  /// The file or directory was not found
+  pub fn is_not_found(&self) -> bool {
+    matches!(self.kind(), ErrorKind::FileNotFound | ErrorKind::DirectoryNotFound)
+  }
+
+  /// A configuration widget doesn't exist, libgphoto2 reports this as bad parameters
+  pub(crate) fn is_missing_config(&self) -> bool {
+    self.kind() == ErrorKind::BadParameters || self.is_not_found()
+  }
+
+  /// Any I/O error, e.g. a USB transfer failed or the device is gone
+  pub fn is_io(&self) -> bool {
+    matches!(
+      self.kind(),
+      ErrorKind::Io
+        | ErrorKind::IoRead
+        | ErrorKind::IoWrite
+        | ErrorKind::IoUpdate
+        | ErrorKind::IoSerialSpeed
+        | ErrorKind::IoUsbClearHalt
+        | ErrorKind::IoUsbFind
+        | ErrorKind::IoUsbClaim
+        | ErrorKind::IoLock
+    )
+  }
+
   /// Map the gphoto type to an [`ErrorKind`]
   pub fn kind(&self) -> ErrorKind {
     match self.error {
@@ -119,6 +222,7 @@ impl Error {
       libgphoto2_sys::GP_ERROR_IO_USB_CLEAR_HALT => ErrorKind::IoUsbClearHalt,
       libgphoto2_sys::GP_ERROR_IO_USB_FIND => ErrorKind::IoUsbFind,
       libgphoto2_sys::GP_ERROR_IO_LOCK => ErrorKind::IoLock,
+      libgphoto2_sys::GP_ERROR_CANCEL => ErrorKind::Cancelled,
 
       libgphoto2_sys::GP_ERROR => ErrorKind::Other,
       _ => ErrorKind::Other,
@@ -138,19 +242,19 @@ impl Serialize for Error {
 
 impl From<std::io::Error> for Error {
   fn from(err: std::io::Error) -> Self {
-    Self { error: libgphoto2_sys::GP_ERROR_IO, info: Some(err.to_string()) }
+    Self::new(libgphoto2_sys::GP_ERROR_IO, Some(err.to_string()))
   }
 }
 
 impl From<std::ffi::NulError> for Error {
   fn from(err: std::ffi::NulError) -> Self {
-    Self { error: libgphoto2_sys::GP_ERROR_BAD_PARAMETERS, info: Some(err.to_string()) }
+    Self::new(libgphoto2_sys::GP_ERROR_BAD_PARAMETERS, Some(err.to_string()))
   }
 }
 
 impl From<std::num::TryFromIntError> for Error {
   fn from(err: std::num::TryFromIntError) -> Self {
-    Self { error: libgphoto2_sys::GP_ERROR, info: Some(err.to_string()) }
+    Self::new(libgphoto2_sys::GP_ERROR, Some(err.to_string()))
   }
 }
 
@@ -162,7 +266,7 @@ impl From<std::convert::Infallible> for Error {
 
 impl From<String> for Error {
   fn from(message: String) -> Self {
-    Self { error: libgphoto2_sys::GP_ERROR, info: Some(message) }
+    Self::new(libgphoto2_sys::GP_ERROR, Some(message))
   }
 }
 
@@ -180,6 +284,14 @@ impl fmt::Display for Error {
       f.write_fmt(format_args!(" [{}]", error_info))?;
     }
 
+    match (self.function, &self.camera) {
+      (Some(function), Some(camera)) => {
+        f.write_fmt(format_args!(" ({} on {})", function, camera))?
+      }
+      (Some(function), None) => f.write_fmt(format_args!(" ({})", function))?,
+      _ => {}
+    }
+
     Ok(())
   }
 }
@@ -215,7 +327,11 @@ macro_rules! try_gp_internal {
     let ($status, $($out),*) = unsafe {
       $(let mut $out = std::mem::MaybeUninit::uninit();)*
 
-      let status = $crate::Error::check(libgphoto2_sys::$func $args) $($unwrap)*;
+      #[cfg(feature = "tracing")]
+      let _span = tracing::trace_span!(target: "gphoto2", "libgphoto2", function = stringify!($func))
+        .entered();
+
+      let status = $crate::Error::check_call(libgphoto2_sys::$func $args, stringify!($func)) $($unwrap)*;
 
       (status, $($out.assume_init()),*)
     };
@@ -7,9 +7,17 @@ use crate::{
   error::Error,
   helper::{as_ref, char_slice_to_cow, chars_to_string, IntoUnixFd},
   task::{BackgroundPtr, Task},
-  try_gp_internal, Context, Result,
+  try_gp_internal, Camera, Context, Result,
+};
+use std::{
+  borrow::Cow,
+  fmt, fs,
+  hash::{Hash, Hasher},
+  io::{self, Write},
+  os::raw::{c_char, c_int, c_uchar, c_void},
+  panic::{catch_unwind, AssertUnwindSafe},
+  path::{Path, PathBuf},
 };
-use std::{borrow::Cow, fmt, fs, path::Path};
 
 /// Represents a path of a file on a camera
 pub struct CameraFilePath {
@@ -17,11 +25,12 @@ pub struct CameraFilePath {
 }
 
 /// Type of a file
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum FileType {
   /// Preview of an image
   Preview,
-  /// Normal fil
+  /// Normal file
   Normal,
   /// Raw data before postprocessing of driver, RAW image files are usually [`FileType::Normal`]
   Raw,
@@ -36,9 +45,13 @@ pub enum FileType {
 /// File on a camera
 ///
 /// To download the file use [`CameraFS`](crate::filesys::CameraFS)
+///
+/// In-memory files implement [`Read`](io::Read) and [`Seek`](io::Seek), so they can be passed
+/// directly to decoders or [`io::copy`] without copying the data into a [`Vec`] first.
 pub struct CameraFile {
   pub(crate) inner: BackgroundPtr<libgphoto2_sys::CameraFile>,
   pub(crate) is_from_disk: bool,
+  position: usize,
 }
 
 impl Drop for CameraFile {
@@ -53,7 +66,7 @@ impl Clone for CameraFile {
   fn clone(&self) -> Self {
     try_gp_internal!(gp_file_ref(*self.inner).unwrap());
 
-    Self { inner: self.inner, is_from_disk: self.is_from_disk }
+    Self { inner: self.inner, is_from_disk: self.is_from_disk, position: self.position }
   }
 }
 
@@ -116,6 +129,19 @@ as_ref!(CameraFile -> libgphoto2_sys::CameraFile, **self.inner);
 as_ref!(CameraFilePath -> libgphoto2_sys::CameraFilePath, self.inner);
 
 impl CameraFilePath {
+  /// Create a path from the folder and the basename of a file
+  ///
+  /// Fails if the folder or the name are too long for libgphoto2 or contain a nul byte.
+  pub fn new(folder: &str, name: &str) -> Result<Self> {
+    // Safety: CameraFilePath only contains character arrays
+    let mut inner: Box<libgphoto2_sys::CameraFilePath> = Box::new(unsafe { std::mem::zeroed() });
+
+    copy_to_char_slice(folder, &mut inner.folder)?;
+    copy_to_char_slice(name, &mut inner.name)?;
+
+    Ok(Self { inner })
+  }
+
   /// Get the name of the file's folder
   pub fn folder(&self) -> Cow<str> {
     char_slice_to_cow(&self.inner.folder)
@@ -125,24 +151,466 @@ impl CameraFilePath {
   pub fn name(&self) -> Cow<str> {
     char_slice_to_cow(&self.inner.name)
   }
+
+  /// Get the extension of the file name without the dot, e.g. `JPG` for `DSC_0001.JPG`
+  ///
+  /// Returns `None` if the name has no extension or starts with the only dot.
+  pub fn extension(&self) -> Option<String> {
+    match self.name().rsplit_once('.') {
+      Some((stem, extension)) if !stem.is_empty() => Some(extension.to_owned()),
+      _ => None,
+    }
+  }
+}
+
+impl CameraFilePath {
+  /// Download the file to `dest` on the host and return the path it was saved to
+  ///
+  ///  - If `dest` is an existing directory or ends with a path separator, the file is saved in
+  ///    that directory with its name on the camera
+  ///  - `dest` may contain the placeholders `{name}`, `{stem}` and `{ext}`, which are replaced
+  ///    with the name of the file on the camera, its name without extension and its extension
+  ///  - Missing parent directories are created
+  ///
+  /// Fails with [`ErrorKind::FileExists`](crate::error::ErrorKind::FileExists) if the file
+  /// already exists, see [`CameraFilePath::download_to_unique`].
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let file = camera.capture_image().wait()?;
+  ///
+  /// // Saved as captures/2024-06-01/DSC_0001.JPG
+  /// file.download_to(&camera, "captures/2024-06-01/")?;
+  ///
+  /// // Saved as raw/DSC_0001-original.NEF
+  /// file.download_to(&camera, "raw/{stem}-original.{ext}")?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_to(&self, camera: &Camera, dest: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = self.local_path(dest.as_ref());
+    self.download_file(camera, &path)?;
+
+    Ok(path)
+  }
+
+  /// Like [`CameraFilePath::download_to`], but appends a counter to the file name
+  /// (e.g. `DSC_0001-1.JPG`) instead of failing if the file already exists
+  pub fn download_to_unique(&self, camera: &Camera, dest: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = unique_path(self.local_path(dest.as_ref()));
+    self.download_file(camera, &path)?;
+
+    Ok(path)
+  }
+
+  /// Download the file into an already open host file
+  ///
+  /// The data is written at the current position of `file`, which may also be a pipe or an
+  /// anonymous file (e.g. opened with `O_TMPFILE`). `file` is closed once the download is complete,
+  /// pass a [`File::try_clone`](fs::File::try_clone) to keep using it afterwards.
+  /// On unix, a raw file descriptor can be converted with `File::from(OwnedFd)`.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  /// use std::io::{Read, Seek};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let file = camera.capture_image().wait()?;
+  ///
+  /// let mut temp = std::fs::File::options().read(true).write(true).create(true).open("/tmp/capture")?;
+  /// file.download_to_file(&camera, temp.try_clone()?)?;
+  ///
+  /// let mut data = Vec::new();
+  /// temp.rewind()?;
+  /// temp.read_to_end(&mut data)?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_to_file(&self, camera: &Camera, file: fs::File) -> Result<()> {
+    camera.fs().download_to_file(&self.folder(), &self.name(), file).wait()?;
+
+    Ok(())
+  }
+
+  /// Move the file from the camera to `dest` on the host and return the path it was saved to
+  ///
+  /// The file is downloaded next to the destination with a `.part` suffix (or `.1.part`,
+  /// `.2.part`, ... if that file already exists), synced to disk and moved to the destination
+  /// without replacing existing files, and only then deleted from the camera. If anything fails before the file
+  /// is stored, it stays on the camera. If deleting the file fails, the downloaded file is
+  /// kept and the error is returned.
+  ///
+  /// `dest` is handled like in [`CameraFilePath::download_to`].
+  pub fn take(&self, camera: &Camera, dest: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = self.local_path(dest.as_ref());
+    let exists_error = || {
+      Error::new(
+        libgphoto2_sys::GP_ERROR_FILE_EXISTS,
+        Some(format!("{} already exists", path.display())),
+      )
+    };
+
+    if path.exists() {
+      return Err(exists_error());
+    }
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let (partial_path, partial_file) = create_partial(&path)?;
+
+    if let Err(error) = self.download_to_file(camera, partial_file) {
+      fs::remove_file(&partial_path).ok();
+      return Err(error);
+    }
+
+    if let Err(error) = persist(&partial_path, &path) {
+      fs::remove_file(&partial_path).ok();
+
+      // The destination may have been created while downloading
+      return Err(match error.kind() {
+        io::ErrorKind::AlreadyExists => exists_error(),
+        _ => error.into(),
+      });
+    }
+
+    camera.fs().delete_file(&self.folder(), &self.name()).wait()?;
+
+    Ok(path)
+  }
+
+  /// Download the file to `dest` on the host through a memory map
+  ///
+  /// The destination file is created with the size of the file on the camera and the data is
+  /// read directly into a memory map of it, without intermediate buffers. This speeds up
+  /// downloading large videos, especially to slow disks and network mounts.
+  ///
+  /// `dest` is handled like in [`CameraFilePath::download_to`]. The driver must support reading
+  /// parts of files (e.g. PTP cameras), otherwise an error of kind
+  /// [`ErrorKind::NotSupported`](crate::error::ErrorKind::NotSupported) is returned.
+  /// Other processes must not modify the file during the download.
+  ///
+  /// Requires the `mmap` feature.
+  #[cfg(feature = "mmap")]
+  pub fn download_mmap(&self, camera: &Camera, dest: impl AsRef<Path>) -> Result<PathBuf> {
+    let (folder, name) = (self.folder(), self.name());
+
+    let size = camera
+      .fs()
+      .file_info(&folder, &name)
+      .wait()?
+      .file()
+      .size()
+      .ok_or_else(|| Error::not_supported("The camera doesn't report the size of the file"))?;
+
+    let path = self.local_path(dest.as_ref());
+
+    if path.is_file() {
+      return Err(Error::new(libgphoto2_sys::GP_ERROR_FILE_EXISTS, None));
+    }
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let file = fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+
+    let result = (|| -> Result<()> {
+      file.set_len(size)?;
+
+      if size == 0 {
+        return Ok(());
+      }
+
+      // Safety: The file was just created and must not be modified by other processes
+      let map = unsafe { memmap2::MmapMut::map_mut(&file)? };
+      let (map, read) = camera.fs().read_into_map(&folder, &name, map).wait()?;
+      map.flush()?;
+      drop(map);
+
+      // The file became smaller since its size was queried
+      if read < usize::try_from(size)? {
+        file.set_len(read.try_into()?)?;
+      }
+
+      Ok(())
+    })();
+
+    if let Err(error) = result {
+      fs::remove_file(&path).ok();
+      return Err(error);
+    }
+
+    Ok(path)
+  }
+
+  fn local_path(&self, dest: &Path) -> PathBuf {
+    let name = self.name();
+
+    let dest = match dest.to_str() {
+      Some(dest) => PathBuf::from(render_name_template(dest, &name)),
+      None => dest.to_owned(),
+    };
+
+    let ends_with_separator =
+      dest.to_str().and_then(|dest| dest.chars().last()).is_some_and(std::path::is_separator);
+
+    if ends_with_separator || dest.is_dir() {
+      dest.join(&*name)
+    } else {
+      dest
+    }
+  }
+
+  fn download_file(&self, camera: &Camera, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    camera.fs().download_to(&self.folder(), &self.name(), path).wait()?;
+
+    Ok(())
+  }
+}
+
+/// Replace the `{name}`, `{stem}` and `{ext}` placeholders of a file name template
+pub(crate) fn render_name_template(template: &str, name: &str) -> String {
+  let path = Path::new(name);
+  let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(name);
+  let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+
+  template.replace("{name}", name).replace("{stem}", stem).replace("{ext}", ext)
+}
+
+/// Create a new file next to `path` to download it into, never reusing an existing file
+fn create_partial(path: &Path) -> io::Result<(PathBuf, fs::File)> {
+  let name = path.file_name().unwrap_or_default();
+
+  for counter in 0..100 {
+    let mut partial_name = name.to_owned();
+    if counter > 0 {
+      partial_name.push(format!(".{counter}"));
+    }
+    partial_name.push(".part");
+
+    let partial_path = path.with_file_name(partial_name);
+
+    match fs::File::options().write(true).create_new(true).open(&partial_path) {
+      Ok(file) => return Ok((partial_path, file)),
+      Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(error) => return Err(error),
+    }
+  }
+
+  Err(io::Error::new(
+    io::ErrorKind::AlreadyExists,
+    format!("Too many partial downloads of {}", path.display()),
+  ))
+}
+
+/// Sync `partial_path` to disk and move it to `path`, fails if `path` already exists
+fn persist(partial_path: &Path, path: &Path) -> io::Result<()> {
+  fs::File::open(partial_path)?.sync_all()?;
+
+  // Unlike a rename, linking never replaces an existing file
+  match fs::hard_link(partial_path, path) {
+    Ok(()) => fs::remove_file(partial_path)?,
+    Err(error) if error.kind() == io::ErrorKind::AlreadyExists => return Err(error),
+    // Some filesystems (e.g. FAT) don't support hard links
+    Err(_) => {
+      let mut dest = fs::File::options().write(true).create_new(true).open(path)?;
+      let copied =
+        io::copy(&mut fs::File::open(partial_path)?, &mut dest).and_then(|_| dest.sync_all());
+
+      if let Err(error) = copied {
+        fs::remove_file(path).ok();
+        return Err(error);
+      }
+
+      fs::remove_file(partial_path)?;
+    }
+  }
+
+  // Make sure the new directory entry is durable before the file is deleted from the camera
+  #[cfg(unix)]
+  {
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+    fs::File::open(parent.unwrap_or(Path::new(".")))?.sync_all()?;
+  }
+
+  Ok(())
+}
+
+/// Append a counter to the file name until the path doesn't exist
+fn unique_path(path: PathBuf) -> PathBuf {
+  if !path.exists() {
+    return path;
+  }
+
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+  let ext = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+
+  (1..)
+    .map(|n| path.with_file_name(format!("{stem}-{n}{ext}")))
+    .find(|candidate| !candidate.exists())
+    .unwrap()
+}
+
+impl fmt::Display for CameraFilePath {
+  /// Formats the full path of the file, e.g. `/store_00010001/DCIM/100NIKON/DSC_0001.JPG`
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let folder = self.folder();
+
+    if folder.ends_with('/') {
+      write!(f, "{}{}", folder, self.name())
+    } else {
+      write!(f, "{}/{}", folder, self.name())
+    }
+  }
+}
+
+impl Clone for CameraFilePath {
+  fn clone(&self) -> Self {
+    Self { inner: Box::new(*self.inner) }
+  }
+}
+
+// Characters after the terminating nul byte are ignored by all comparisons
+impl PartialEq for CameraFilePath {
+  fn eq(&self, other: &Self) -> bool {
+    self.folder() == other.folder() && self.name() == other.name()
+  }
+}
+
+impl Eq for CameraFilePath {}
+
+impl Hash for CameraFilePath {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.folder().hash(state);
+    self.name().hash(state);
+  }
+}
+
+impl TryFrom<(&str, &str)> for CameraFilePath {
+  type Error = Error;
+
+  fn try_from((folder, name): (&str, &str)) -> Result<Self> {
+    Self::new(folder, name)
+  }
+}
+
+impl TryFrom<(String, String)> for CameraFilePath {
+  type Error = Error;
+
+  fn try_from((folder, name): (String, String)) -> Result<Self> {
+    Self::new(&folder, &name)
+  }
+}
+
+impl From<&CameraFilePath> for (String, String) {
+  fn from(path: &CameraFilePath) -> Self {
+    (path.folder().into_owned(), path.name().into_owned())
+  }
+}
+
+impl From<CameraFilePath> for (String, String) {
+  fn from(path: CameraFilePath) -> Self {
+    Self::from(&path)
+  }
+}
+
+/// Copy `value` into a nul terminated character array
+fn copy_to_char_slice(value: &str, chars: &mut [c_char]) -> Result<()> {
+  if value.len() >= chars.len() || value.contains('\0') {
+    return Err(Error::bad_parameters(format!("Invalid file path component {value:?}")));
+  }
+
+  for (c, byte) in chars.iter_mut().zip(value.bytes()) {
+    *c = c_char::from_ne_bytes([byte]);
+  }
+
+  Ok(())
 }
 
 impl CameraFile {
+  pub(crate) fn from_ptr(ptr: *mut libgphoto2_sys::CameraFile, is_from_disk: bool) -> Self {
+    Self { inner: BackgroundPtr(ptr), is_from_disk, position: 0 }
+  }
+
   pub(crate) fn new() -> Result<Self> {
     try_gp_internal!(gp_file_new(&out camera_file_ptr)?);
 
-    Ok(Self { inner: BackgroundPtr(camera_file_ptr), is_from_disk: false })
+    Ok(Self::from_ptr(camera_file_ptr, false))
   }
 
   pub(crate) fn new_file(path: &Path) -> Result<Self> {
-    if path.is_file() {
-      return Err(Error::new(libgphoto2_sys::GP_ERROR_FILE_EXISTS, None));
-    }
+    // Never truncate an existing file, even if it was created after a check
+    let file = match fs::File::options().write(true).create_new(true).open(path) {
+      Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+        return Err(Error::new(libgphoto2_sys::GP_ERROR_FILE_EXISTS, None))
+      }
+      file => file?,
+    };
+
+    Self::from_file(file)
+  }
+
+  /// Create an in-memory file containing a copy of `data`
+  pub fn from_data(data: &[u8]) -> Result<Self> {
+    let camera_file = Self::new()?;
 
-    let fd = fs::File::create(path)?.into_unix_fd();
+    try_gp_internal!(gp_file_append(
+      *camera_file.inner,
+      data.as_ptr().cast(),
+      data.len().try_into()?
+    )?);
+
+    Ok(camera_file)
+  }
+
+  /// Create a file backed by an open host file
+  ///
+  /// Data downloaded into this file is written directly to `file`.
+  pub fn from_file(file: fs::File) -> Result<Self> {
+    let fd = file.into_unix_fd();
 
     try_gp_internal!(gp_file_new_from_fd(&out camera_file_ptr, fd)?);
-    Ok(Self { inner: BackgroundPtr(camera_file_ptr), is_from_disk: true })
+    Ok(Self::from_ptr(camera_file_ptr, true))
+  }
+
+  /// Create a file backed by a file descriptor
+  ///
+  /// libgphoto2 takes ownership of the file descriptor and closes it when the file is dropped.
+  #[cfg(unix)]
+  pub fn from_fd(fd: impl std::os::unix::io::IntoRawFd) -> Result<Self> {
+    try_gp_internal!(gp_file_new_from_fd(&out camera_file_ptr, fd.into_raw_fd())?);
+    Ok(Self::from_ptr(camera_file_ptr, true))
+  }
+
+  /// Borrow the data of an in-memory file without copying it
+  ///
+  /// Returns [`ErrorKind::NotSupported`](crate::error::ErrorKind::NotSupported) for files stored on disk,
+  /// use [`CameraFile::get_data`] for those.
+  pub fn data(&self) -> Result<&[u8]> {
+    if self.is_from_disk {
+      return Err(Error::new(
+        libgphoto2_sys::GP_ERROR_NOT_SUPPORTED,
+        Some("File is not stored in memory".to_owned()),
+      ));
+    }
+
+    try_gp_internal!(gp_file_get_data_and_size(*self.inner, &out data, &out size)?);
+
+    if data.is_null() {
+      return Ok(&[]);
+    }
+
+    Ok(unsafe { std::slice::from_raw_parts(data.cast::<u8>(), size.try_into()?) })
   }
 
   /// Get the data of the file
@@ -205,6 +673,45 @@ impl CameraFile {
   }
 }
 
+impl io::Read for CameraFile {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let data = self.data().map_err(io::Error::other)?;
+    let remaining = data.get(self.position..).unwrap_or_default();
+
+    let len = remaining.len().min(buf.len());
+    buf[..len].copy_from_slice(&remaining[..len]);
+    self.position += len;
+
+    Ok(len)
+  }
+}
+
+impl io::Seek for CameraFile {
+  fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+    let (base, offset) = match pos {
+      io::SeekFrom::Start(offset) => (0, i64::try_from(offset).ok()),
+      io::SeekFrom::End(offset) => (self.data().map_err(io::Error::other)?.len(), Some(offset)),
+      io::SeekFrom::Current(offset) => (self.position, Some(offset)),
+    };
+
+    let position = i64::try_from(base)
+      .ok()
+      .zip(offset)
+      .and_then(|(base, offset)| base.checked_add(offset))
+      .and_then(|position| usize::try_from(position).ok())
+      .ok_or_else(|| {
+        io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "Invalid seek to a negative or overflowing position",
+        )
+      })?;
+
+    self.position = position;
+
+    position.try_into().map_err(io::Error::other)
+  }
+}
+
 impl fmt::Debug for CameraFile {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     f.debug_struct("CameraFile")
@@ -214,3 +721,317 @@ impl fmt::Debug for CameraFile {
       .finish()
   }
 }
+
+/// Destination of a streamed download
+///
+/// Implement this to stream files from the camera into sockets, compression encoders or
+/// upload streams without intermediate files, see
+/// [`CameraFS::download_to_sink`](crate::filesys::CameraFS::download_to_sink).
+/// Any [`Write`] can be used with
+/// [`CameraFS::download_to_writer`](crate::filesys::CameraFS::download_to_writer) instead.
+pub trait FileSink {
+  /// Called once before the first chunk with the size of the file, if the camera reports it
+  fn size_hint(&mut self, _size: Option<u64>) {}
+
+  /// Write a chunk of the file
+  fn write(&mut self, data: &[u8]) -> io::Result<()>;
+
+  /// Called once after the last chunk was written
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// Adapts a [`Write`] to a [`FileSink`]
+pub(crate) struct WriterSink<W>(pub(crate) W);
+
+impl<W: Write> FileSink for WriterSink<W> {
+  fn write(&mut self, data: &[u8]) -> io::Result<()> {
+    self.0.write_all(data)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.0.flush()
+  }
+}
+
+/// Streams data written to a [`CameraFile`] into a [`FileSink`]
+///
+/// Uses `gp_file_new_from_handler`, so no data is buffered by libgphoto2.
+pub(crate) struct SinkHandler<S> {
+  handler: libgphoto2_sys::CameraFileHandler,
+  sink: S,
+  written: u64,
+  error: Option<Error>,
+}
+
+impl<S: FileSink> SinkHandler<S> {
+  pub(crate) fn new(sink: S) -> Self {
+    Self {
+      handler: libgphoto2_sys::CameraFileHandler {
+        size: Some(Self::size_func),
+        read: Some(Self::read_func),
+        write: Some(Self::write_func),
+      },
+      sink,
+      written: 0,
+      error: None,
+    }
+  }
+
+  /// Creates a [`CameraFile`] backed by this handler and passes it to `f`.
+  ///
+  /// The file must not be cloned, it is freed before this function returns,
+  /// which guarantees that libgphoto2 never calls into a dangling handler.
+  pub(crate) fn with_file<T>(&mut self, f: impl FnOnce(&CameraFile) -> Result<T>) -> Result<T> {
+    let handler_ptr: *mut Self = self;
+
+    try_gp_internal!(gp_file_new_from_handler(
+      &out file_ptr,
+      std::ptr::addr_of_mut!((*handler_ptr).handler),
+      handler_ptr.cast::<c_void>()
+    )?);
+
+    let camera_file = CameraFile::from_ptr(file_ptr, false);
+    let result = f(&camera_file);
+    drop(camera_file);
+
+    // Errors of the writer are more descriptive than the generic libgphoto2 I/O errors.
+    match self.error.take() {
+      Some(error) => Err(error),
+      None => result,
+    }
+  }
+
+  pub(crate) fn into_sink(self) -> S {
+    self.sink
+  }
+
+  unsafe fn from_ptr<'a>(ptr: *mut c_void) -> &'a mut Self {
+    &mut *ptr.cast::<Self>()
+  }
+
+  unsafe extern "C" fn size_func(handler: *mut c_void, size: *mut u64) -> c_int {
+    *size = Self::from_ptr(handler).written;
+    0
+  }
+
+  unsafe extern "C" fn read_func(
+    _handler: *mut c_void,
+    _data: *mut c_uchar,
+    _len: *mut u64,
+  ) -> c_int {
+    libgphoto2_sys::GP_ERROR_NOT_SUPPORTED
+  }
+
+  unsafe extern "C" fn write_func(
+    handler: *mut c_void,
+    data: *mut c_uchar,
+    len: *mut u64,
+  ) -> c_int {
+    let handler = Self::from_ptr(handler);
+
+    let data = match usize::try_from(*len) {
+      Ok(len) => std::slice::from_raw_parts(data, len),
+      Err(error) => {
+        handler.error = Some(error.into());
+        return libgphoto2_sys::GP_ERROR_IO_WRITE;
+      }
+    };
+
+    match catch_unwind(AssertUnwindSafe(|| handler.sink.write(data))) {
+      Ok(Ok(())) => {
+        handler.written += *len;
+        0
+      }
+      Ok(Err(error)) => {
+        handler.error = Some(error.into());
+        libgphoto2_sys::GP_ERROR_IO_WRITE
+      }
+      Err(_) => {
+        handler.error = Some(io::Error::other("Sink panicked").into());
+        libgphoto2_sys::GP_ERROR_IO_WRITE
+      }
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{persist, render_name_template, CameraFilePath, FileSink};
+  use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+  use std::fs;
+
+  #[test]
+  fn test_file_path() {
+    use std::collections::HashSet;
+
+    let path = CameraFilePath::try_from(("/store_00010001/DCIM", "IMG_0001.JPG")).unwrap();
+    assert_eq!(path.to_string(), "/store_00010001/DCIM/IMG_0001.JPG");
+    assert_eq!(path.extension().as_deref(), Some("JPG"));
+    assert_eq!(CameraFilePath::new("/", "IMG_0001").unwrap().to_string(), "/IMG_0001");
+    assert_eq!(CameraFilePath::new("/", "IMG_0001").unwrap().extension(), None);
+
+    let paths = HashSet::from([path.clone(), path.clone()]);
+    assert_eq!(paths.len(), 1);
+
+    let (folder, name): (String, String) = path.into();
+    assert_eq!((folder.as_str(), name.as_str()), ("/store_00010001/DCIM", "IMG_0001.JPG"));
+
+    assert!(CameraFilePath::new("/", &"a".repeat(1000)).is_err());
+  }
+
+  #[test]
+  fn test_download_to() {
+    let camera = crate::sample_camera();
+    let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+
+    let path = file.download_to(&camera, dir.join("nested/")).unwrap();
+    assert_eq!(path, dir.join("nested/blank.jpg"));
+    assert_eq!(fs::read(&path).unwrap(), SAMPLE_IMAGE);
+
+    let error = file.download_to(&camera, dir.join("nested")).unwrap_err();
+    assert_eq!(error.kind(), crate::error::ErrorKind::FileExists);
+
+    let path = file.download_to_unique(&camera, dir.join("nested/{stem}.{ext}")).unwrap();
+    assert_eq!(path, dir.join("nested/blank-1.jpg"));
+  }
+
+  #[test]
+  fn test_download_to_file() {
+    use std::io::{Read, Seek};
+
+    let camera = crate::sample_camera();
+    let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+    let mut dest = tempfile::tempfile().unwrap();
+    file.download_to_file(&camera, dest.try_clone().unwrap()).unwrap();
+
+    let mut data = Vec::new();
+    dest.rewind().unwrap();
+    dest.read_to_end(&mut data).unwrap();
+    assert_eq!(data, SAMPLE_IMAGE);
+  }
+
+  #[test]
+  fn test_download_to_sink() {
+    #[derive(Default)]
+    struct Sink {
+      size: Option<u64>,
+      data: Vec<u8>,
+      flushed: bool,
+    }
+
+    impl FileSink for Sink {
+      fn size_hint(&mut self, size: Option<u64>) {
+        self.size = size;
+      }
+
+      fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.data.extend_from_slice(data);
+        Ok(())
+      }
+
+      fn flush(&mut self) -> std::io::Result<()> {
+        self.flushed = true;
+        Ok(())
+      }
+    }
+
+    let camera = crate::sample_camera();
+    let sink = camera
+      .fs()
+      .download_to_sink("/store_00010001/foo", "blank.jpg", Sink::default())
+      .wait()
+      .unwrap();
+
+    assert_eq!(sink.size, Some(SAMPLE_IMAGE.len().try_into().unwrap()));
+    assert_eq!(sink.data, SAMPLE_IMAGE);
+    assert!(sink.flushed);
+  }
+
+  #[test]
+  fn test_take_missing_file() {
+    let camera = crate::sample_camera();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+
+    // Capturing or uploading would change the file tree snapshotted by test_fs,
+    // so only check that nothing is left behind if the download fails.
+    let file = CameraFilePath::new("/store_00010001/foo", "missing.jpg").unwrap();
+    assert!(file.take(&camera, dir.join("")).is_err());
+
+    assert!(!dir.join("missing.jpg").exists());
+    assert!(!dir.join("missing.jpg.part").exists());
+  }
+
+  #[cfg(feature = "test-utils")]
+  #[test]
+  fn test_take() {
+    // Taking deletes the file from the camera, so use a copy of the virtual camera files
+    let camera_dir = tempfile::tempdir().unwrap();
+    fs::create_dir(camera_dir.path().join("foo")).unwrap();
+    fs::write(camera_dir.path().join("foo/blank.jpg"), SAMPLE_IMAGE).unwrap();
+
+    let camera = crate::sample_context().virtual_camera(camera_dir.path()).unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+
+    // Not a left over partial download, but a file of the user
+    fs::write(dir.join("blank.jpg.part"), "user data").unwrap();
+
+    let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+    let path = file.take(&camera, dir.join("")).unwrap();
+
+    assert_eq!(path, dir.join("blank.jpg"));
+    assert_eq!(fs::read(&path).unwrap(), SAMPLE_IMAGE);
+    assert_eq!(fs::read(dir.join("blank.jpg.part")).unwrap(), b"user data");
+    assert!(!dir.join("blank.jpg.1.part").exists());
+
+    let files: Vec<_> = camera.fs().list_files("/store_00010001/foo").wait().unwrap().collect();
+    assert!(!files.iter().any(|name| name == "blank.jpg"));
+  }
+
+  #[test]
+  fn test_render_name_template() {
+    assert_eq!(render_name_template("{name}", "DSC_0001.JPG"), "DSC_0001.JPG");
+    assert_eq!(render_name_template("shoot-{stem}.{ext}", "DSC_0001.JPG"), "shoot-DSC_0001.JPG");
+    assert_eq!(render_name_template("{stem}-{stem}", "archive.tar.gz"), "archive.tar-archive.tar");
+    assert_eq!(render_name_template("{stem}.{ext}", "README"), "README.");
+    assert_eq!(render_name_template("fixed.jpg", "DSC_0001.JPG"), "fixed.jpg");
+  }
+
+  #[cfg(feature = "mmap")]
+  #[test]
+  fn test_download_mmap() {
+    let camera = crate::sample_camera();
+    let dir = tempfile::tempdir().unwrap();
+    let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+
+    let path = file.download_mmap(&camera, dir.path()).unwrap();
+    assert_eq!(path, dir.path().join("blank.jpg"));
+    assert_eq!(fs::read(&path).unwrap(), SAMPLE_IMAGE);
+
+    let error = file.download_mmap(&camera, dir.path()).unwrap_err();
+    assert_eq!(error.kind(), crate::error::ErrorKind::FileExists);
+  }
+
+  #[test]
+  fn test_persist() {
+    let dir = tempfile::tempdir().unwrap();
+    let (partial_path, path) = (dir.path().join("image.jpg.part"), dir.path().join("image.jpg"));
+
+    fs::write(&partial_path, "new").unwrap();
+    persist(&partial_path, &path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert!(!partial_path.exists());
+
+    // Never replace an existing file
+    fs::write(&partial_path, "newer").unwrap();
+    let error = persist(&partial_path, &path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(fs::read_to_string(&partial_path).unwrap(), "newer");
+  }
+}
@@ -1,14 +1,22 @@
 //! Camera filesystem and storages
 
 use crate::{
-  file::{CameraFile, FileType},
+  file::{CameraFile, FileSink, FileType, SinkHandler, WriterSink},
   helper::{bitflags, char_slice_to_cow, to_c_string, UninitBox},
   list::{CameraList, FileListIter},
-  task::Task,
+  task::{BackgroundPtr, Task},
   try_gp_internal, Camera, Error, Result,
 };
 use libgphoto2_sys::time_t;
-use std::{borrow::Cow, ffi, fmt, fs, path::Path};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+use std::{
+  borrow::Cow,
+  ffi, fmt, fs,
+  io::{Read, Write},
+  path::Path,
+  sync::{Arc, Mutex},
+};
 
 macro_rules! storage_info {
   ($(# $attr:tt)* $name:ident: $bitflag_ty:ident, |$inner:ident: $inner_ty:ident| { $($(# $field_attr:tt)* $field:ident: $ty:ty = $bitflag:ident, $expr:expr;)* }) => {
@@ -41,6 +49,7 @@ macro_rules! storage_info {
     }
 
     impl fmt::Debug for $name {
+      #[allow(deprecated)]
       fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct(stringify!($name))
           $(
@@ -49,11 +58,24 @@ macro_rules! storage_info {
           .finish()
       }
     }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for $name {
+      #[allow(deprecated)]
+      fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let fields: &[&str] = &[$(stringify!($field)),*];
+        let mut s = serializer.serialize_struct(stringify!($name), fields.len())?;
+        $(
+          s.serialize_field(stringify!($field), &self.$field())?;
+        )*
+        s.end()
+      }
+    }
   };
 }
 
 /// Hardware storage type
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum StorageType {
   /// Unknown storage type
@@ -69,7 +91,7 @@ pub enum StorageType {
 }
 
 /// Type of the filesystem hierarchy
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum FilesystemType {
   /// Unknown filesystem type
@@ -83,7 +105,7 @@ pub enum FilesystemType {
 }
 
 /// Access types of storage
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AccessType {
   /// Read/Write
@@ -113,6 +135,25 @@ bitflags!(
   }
 );
 
+impl FilePermissions {
+  /// Create new file permissions
+  pub fn new(read: bool, delete: bool) -> Self {
+    use libgphoto2_sys::CameraFilePermissions as GPPermissions;
+
+    let mut permissions = GPPermissions::GP_FILE_PERM_NONE;
+
+    if read {
+      permissions |= GPPermissions::GP_FILE_PERM_READ;
+    }
+
+    if delete {
+      permissions |= GPPermissions::GP_FILE_PERM_DELETE;
+    }
+
+    Self(permissions)
+  }
+}
+
 storage_info!(
   /// Image thumbnail information
   FileInfoPreview: CameraFileInfoFields, |info: CameraFileInfoPreview| {
@@ -165,9 +206,15 @@ storage_info!(
 pub struct FileInfo {
   // It's fairly large, so we want to keep it on the heap.
   pub(crate) inner: Box<libgphoto2_sys::CameraFileInfo>,
+  /// Fields changed with the setters
+  changed: libgphoto2_sys::CameraFileInfoFields,
 }
 
 impl FileInfo {
+  pub(crate) fn from_inner(inner: Box<libgphoto2_sys::CameraFileInfo>) -> Self {
+    Self { inner, changed: libgphoto2_sys::CameraFileInfoFields::GP_FILE_INFO_NONE }
+  }
+
   /// Info for file preview
   pub fn preview(&self) -> &FileInfoPreview {
     FileInfoPreview::from_inner_ref(&self.inner.preview)
@@ -182,6 +229,61 @@ impl FileInfo {
   pub fn audio(&self) -> &FileInfoAudio {
     FileInfoAudio::from_inner_ref(&self.inner.audio)
   }
+
+  /// Set the permissions of the file
+  ///
+  /// The change is applied with [`CameraFS::set_file_info`].
+  pub fn set_permissions(&mut self, permissions: FilePermissions) {
+    use libgphoto2_sys::CameraFileInfoFields as Fields;
+
+    self.inner.file.permissions = permissions.0;
+    self.inner.file.fields |= Fields::GP_FILE_INFO_PERMISSIONS;
+    self.changed |= Fields::GP_FILE_INFO_PERMISSIONS;
+  }
+
+  /// Set the modification time of the file
+  ///
+  /// The change is applied with [`CameraFS::set_file_info`].
+  pub fn set_mtime(&mut self, mtime: time_t) {
+    use libgphoto2_sys::CameraFileInfoFields as Fields;
+
+    self.inner.file.mtime = mtime;
+    self.inner.file.fields |= Fields::GP_FILE_INFO_MTIME;
+    self.changed |= Fields::GP_FILE_INFO_MTIME;
+  }
+
+  /// Info containing only the fields changed with the setters
+  ///
+  /// libgphoto2 rejects setting read-only fields like the size or mime type, so the info
+  /// read from the camera can't be sent back as is.
+  pub(crate) fn changes(&self) -> libgphoto2_sys::CameraFileInfo {
+    use libgphoto2_sys::CameraFileInfoFields as Fields;
+
+    // Safety: CameraFileInfo is plain data, all zeroes is an info without fields
+    let mut changes: libgphoto2_sys::CameraFileInfo = unsafe { std::mem::zeroed() };
+    changes.file.fields = self.changed;
+
+    if (self.changed & Fields::GP_FILE_INFO_PERMISSIONS).0 != 0 {
+      changes.file.permissions = self.inner.file.permissions;
+    }
+
+    if (self.changed & Fields::GP_FILE_INFO_MTIME).0 != 0 {
+      changes.file.mtime = self.inner.file.mtime;
+    }
+
+    changes
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FileInfo {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("FileInfo", 3)?;
+    s.serialize_field("preview", self.preview())?;
+    s.serialize_field("file", self.file())?;
+    s.serialize_field("audio", self.audio())?;
+    s.end()
+  }
 }
 
 impl fmt::Debug for FileInfo {
@@ -194,6 +296,186 @@ impl fmt::Debug for FileInfo {
   }
 }
 
+/// Chunked reader of a file on the camera
+///
+/// Created by [`CameraFS::read_chunks`]. Every chunk is read with a separate
+/// `gp_camera_file_read` call on the libgphoto2 thread, so the chunks can be awaited one by
+/// one (e.g. to stream a photo to an HTTP client while it's still being read from the camera).
+///
+/// Every call to [`FileChunks::next_chunk`] reserves the next chunk of the file, so tasks
+/// which are waited for in a different order still return consecutive chunks.
+///
+/// The iterator implementation blocks on every chunk and ends after the first error.
+/// With the `futures` feature, [`FileChunks`] also implements
+/// [`Stream`](futures_core::Stream), which reads the chunks without blocking and also ends
+/// after the first error.
+///
+/// ```no_run
+/// use gphoto2::{file::FileType, Context, Result};
+///
+/// # async fn stream() -> Result<()> {
+/// let camera = Context::new()?.autodetect_camera().await?;
+/// let mut chunks = camera.fs().read_chunks("/store_00010001/DCIM/100CANON", "MVI_0001.MOV", FileType::Normal, 1 << 20)?;
+///
+/// while let Some(chunk) = chunks.next_chunk().await? {
+///   println!("Read {} bytes", chunk.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileChunks {
+  camera: Camera,
+  folder: String,
+  file: String,
+  file_type: FileType,
+  chunk_size: usize,
+  cursor: Arc<Mutex<ChunkCursor>>,
+  finished: bool,
+  #[cfg(feature = "futures")]
+  pending: Option<Task<Result<Option<Vec<u8>>>>>,
+}
+
+/// Read position of [`FileChunks`], shared with the tasks reading the chunks
+#[derive(Default)]
+struct ChunkCursor {
+  /// Offset of the next chunk to reserve
+  next: u64,
+  /// Offset of the first chunk which failed, it's read again by the next call
+  failed: Option<u64>,
+  /// Size of the file, known once a chunk reached the end
+  end: Option<u64>,
+}
+
+impl FileChunks {
+  /// Read the next chunk, returns `None` once the end of the file is reached
+  ///
+  /// Unlike the iterator, this can be called again after an error to retry reading the chunk.
+  pub fn next_chunk(&mut self) -> Task<Result<Option<Vec<u8>>>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (self.folder.clone(), self.file.clone());
+    let (file_type, chunk_size, cursor) = (self.file_type, self.chunk_size, self.cursor.clone());
+
+    // Reserve the chunk now, so it doesn't depend on the order the tasks run in
+    let offset = {
+      let mut cursor = self.cursor.lock().unwrap();
+
+      cursor.failed.take().unwrap_or_else(|| {
+        let offset = cursor.next;
+        cursor.next = offset.saturating_add(chunk_size as u64);
+        offset
+      })
+    };
+
+    unsafe {
+      Task::new(move || {
+        if cursor.lock().unwrap().end.is_some_and(|end| offset >= end) {
+          return Ok(None);
+        }
+
+        let mut buffer = vec![0; chunk_size];
+        let mut read = 0;
+
+        // Drivers may return less than requested before the end of the file
+        while read < chunk_size {
+          let part_offset = offset + u64::try_from(read)?;
+
+          match read_file_part(
+            camera,
+            context,
+            &folder,
+            &file,
+            file_type,
+            part_offset,
+            &mut buffer[read..],
+          ) {
+            Ok(0) => break,
+            Ok(size) => read += size,
+            Err(error) => {
+              let mut cursor = cursor.lock().unwrap();
+              cursor.failed = Some(cursor.failed.map_or(offset, |failed| failed.min(offset)));
+
+              return Err(error);
+            }
+          }
+        }
+
+        if read < chunk_size {
+          cursor.lock().unwrap().end = Some(offset + u64::try_from(read)?);
+        }
+
+        if read == 0 {
+          return Ok(None);
+        }
+
+        buffer.truncate(read);
+
+        Ok(Some(buffer))
+      })
+    }
+    .context(context)
+    .for_camera(&self.camera)
+  }
+}
+
+impl Iterator for FileChunks {
+  type Item = Result<Vec<u8>>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.finished {
+      return None;
+    }
+
+    let chunk = self.next_chunk().wait();
+    self.finished = !matches!(chunk, Ok(Some(_)));
+
+    chunk.transpose()
+  }
+}
+
+impl std::iter::FusedIterator for FileChunks {}
+
+#[cfg(feature = "futures")]
+impl futures_core::Stream for FileChunks {
+  type Item = Result<Vec<u8>>;
+
+  fn poll_next(
+    self: std::pin::Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<Option<Self::Item>> {
+    use std::{future::Future, task::Poll};
+
+    let this = self.get_mut();
+
+    if this.finished {
+      return Poll::Ready(None);
+    }
+
+    let mut task = match this.pending.take() {
+      Some(task) => task,
+      None => this.next_chunk(),
+    };
+
+    match std::pin::Pin::new(&mut task).poll(cx) {
+      Poll::Ready(chunk) => {
+        this.finished = !matches!(chunk, Ok(Some(_)));
+        Poll::Ready(chunk.transpose())
+      }
+      Poll::Pending => {
+        this.pending = Some(task);
+        Poll::Pending
+      }
+    }
+  }
+}
+
+#[cfg(feature = "futures")]
+impl futures_core::FusedStream for FileChunks {
+  fn is_terminated(&self) -> bool {
+    self.finished
+  }
+}
+
 /// File system actions for a camera
 pub struct CameraFS<'a> {
   pub(crate) camera: &'a Camera,
@@ -253,10 +535,16 @@ storage_info!(
     filesystem_type: FilesystemType = GP_STORAGEINFO_FILESYSTEMTYPE, info.fstype.into();
     /// Access permissions
     access_type: AccessType = GP_STORAGEINFO_ACCESS, info.access.into();
-    /// Total storage capacity in Kilobytes
-    capacity_kb: u64 = GP_STORAGEINFO_MAXCAPACITY, info.capacitykbytes * 1024;
-    /// Free storage in Kilobytes
-    free_kb: u64 = GP_STORAGEINFO_FREESPACEKBYTES, info.freekbytes * 1024;
+    /// Total storage capacity in bytes, despite the name
+    #[deprecated(note = "returns bytes despite its name, use `capacity_bytes` instead")]
+    capacity_kb: u64 = GP_STORAGEINFO_MAXCAPACITY, info.capacitykbytes.saturating_mul(1024);
+    /// Total storage capacity in bytes
+    capacity_bytes: u64 = GP_STORAGEINFO_MAXCAPACITY, info.capacitykbytes.saturating_mul(1024);
+    /// Free storage in bytes, despite the name
+    #[deprecated(note = "returns bytes despite its name, use `free_bytes` instead")]
+    free_kb: u64 = GP_STORAGEINFO_FREESPACEKBYTES, info.freekbytes.saturating_mul(1024);
+    /// Free storage in bytes
+    free_bytes: u64 = GP_STORAGEINFO_FREESPACEKBYTES, info.freekbytes.saturating_mul(1024);
     /// Number of images that fit in free space (guessed by the camera)
     free_images: u64 = GP_STORAGEINFO_FREESPACEIMAGES, info.freeimages;
   }
@@ -285,6 +573,7 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// Get information of a file
@@ -305,10 +594,54 @@ impl<'a> CameraFS<'a> {
           *context
         )?);
 
-        Ok(FileInfo { inner: inner.assume_init() })
+        Ok(FileInfo::from_inner(inner.assume_init()))
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Set information of a file
+  ///
+  /// Only the fields changed with the setters of [`FileInfo`] are sent to the camera.
+  /// Not all drivers support changing all fields, most only support
+  /// changing the [permissions](FileInfo::set_permissions).
+  ///
+  /// ```no_run
+  /// use gphoto2::{filesys::FilePermissions, Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let fs = camera.fs();
+  ///
+  /// // Protect the file from being deleted
+  /// let mut info = fs.file_info("/store_00010001/DCIM/100CANON", "IMG_0001.JPG").wait()?;
+  /// info.set_permissions(FilePermissions::new(true, false));
+  /// fs.set_file_info("/store_00010001/DCIM/100CANON", "IMG_0001.JPG", &info).wait()?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn set_file_info(&self, folder: &str, file: &str, info: &FileInfo) -> Task<Result<()>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+    let info = info.changes();
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_file_set_info(
+          *camera,
+          to_c_string!(folder),
+          to_c_string!(file),
+          info,
+          *context
+        )?);
+
+        Ok(())
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// Downloads a file from the camera
@@ -316,6 +649,40 @@ impl<'a> CameraFS<'a> {
     self.to_camera_file(folder, file, FileType::Normal, Some(path))
   }
 
+  /// Downloads a file from the camera into an open host file
+  ///
+  /// `dest` is closed when the returned [`CameraFile`] is dropped,
+  /// see [`CameraFilePath::download_to_file`](crate::file::CameraFilePath::download_to_file).
+  pub fn download_to_file(
+    &self,
+    folder: &str,
+    file: &str,
+    dest: fs::File,
+  ) -> Task<Result<CameraFile>> {
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+
+    unsafe {
+      Task::new(move || {
+        let camera_file = CameraFile::from_file(dest)?;
+
+        try_gp_internal!(gp_camera_file_get(
+          *camera,
+          to_c_string!(folder),
+          to_c_string!(file),
+          FileType::Normal.into(),
+          *camera_file.inner,
+          *context
+        )?);
+
+        Ok(camera_file)
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
   /// Downloads a camera file to memory
   pub fn download(&self, folder: &str, file: &str) -> Task<Result<CameraFile>> {
     self.to_camera_file(folder, file, FileType::Normal, None)
@@ -331,6 +698,308 @@ impl<'a> CameraFS<'a> {
     self.to_camera_file(folder, file, FileType::Exif, None)
   }
 
+  /// Downloads a specific [`FileType`] of a file into memory
+  ///
+  /// Check [`FileOperations`](crate::abilities::FileOperations) to see which
+  /// file types are supported by the camera.
+  ///
+  /// ```no_run
+  /// use gphoto2::{file::FileType, Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// // Only fetch the embedded JPEG preview of a RAW file
+  /// let preview = camera
+  ///   .fs()
+  ///   .download_as("/store_00010001/DCIM/100CANON", "IMG_0001.CR2", FileType::Preview)
+  ///   .wait()?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_as(
+    &self,
+    folder: &str,
+    file: &str,
+    file_type: FileType,
+  ) -> Task<Result<CameraFile>> {
+    self.to_camera_file(folder, file, file_type, None)
+  }
+
+  /// Downloads a file from the camera and streams it into a writer
+  ///
+  /// The data is written in chunks as it is received from the camera, without
+  /// buffering the whole file in memory. The writer is returned once the download is complete.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  /// use std::{fs::File, io::BufWriter};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let file = camera.capture_image().wait()?;
+  ///
+  /// let writer = BufWriter::new(File::create("image.jpg")?);
+  /// camera.fs().download_to_writer(&file.folder(), &file.name(), writer).wait()?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_to_writer<W>(&self, folder: &str, file: &str, writer: W) -> Task<Result<W>>
+  where
+    W: Write + Send + 'static,
+  {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+
+    unsafe {
+      Task::new(move || {
+        let sink = download_into_sink(camera, context, &folder, &file, WriterSink(writer))?;
+
+        Ok(sink.0)
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Downloads a file from the camera and streams it into a [`FileSink`]
+  ///
+  /// The size of the file is queried first and passed to [`FileSink::size_hint`], then the data
+  /// is passed to [`FileSink::write`] in chunks as it is received from the camera. The sink
+  /// is returned once the download is complete.
+  ///
+  /// ```no_run
+  /// use gphoto2::{file::FileSink, Context, Result};
+  /// use std::{io, sync::mpsc};
+  ///
+  /// struct ChunkSender(mpsc::Sender<Vec<u8>>);
+  ///
+  /// impl FileSink for ChunkSender {
+  ///   fn write(&mut self, data: &[u8]) -> io::Result<()> {
+  ///     self.0.send(data.to_vec()).map_err(|_| io::ErrorKind::BrokenPipe.into())
+  ///   }
+  /// }
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let file = camera.capture_image().wait()?;
+  ///
+  /// let (sender, receiver) = mpsc::channel();
+  /// let upload = std::thread::spawn(move || receiver.into_iter().map(|chunk| chunk.len()).sum::<usize>());
+  ///
+  /// camera.fs().download_to_sink(&file.folder(), &file.name(), ChunkSender(sender)).wait()?;
+  /// println!("Uploaded {} bytes", upload.join().unwrap());
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_to_sink<S>(&self, folder: &str, file: &str, mut sink: S) -> Task<Result<S>>
+  where
+    S: FileSink + Send + 'static,
+  {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+
+    unsafe {
+      Task::new(move || {
+        let mut info = UninitBox::uninit();
+
+        try_gp_internal!(gp_camera_file_get_info(
+          *camera,
+          to_c_string!(folder),
+          to_c_string!(file),
+          info.as_mut_ptr(),
+          *context
+        )?);
+
+        let info = FileInfo::from_inner(info.assume_init());
+        sink.size_hint(info.file().size());
+
+        download_into_sink(camera, context, &folder, &file, sink)
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Read a part of a file
+  ///
+  /// Reads up to `buffer.len()` bytes starting at `offset` into `buffer` and returns it,
+  /// truncated to the number of bytes actually read. This allows reading large files
+  /// incrementally while reusing the same buffer.
+  ///
+  /// ```no_run
+  /// use gphoto2::{file::FileType, Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  ///
+  /// // Read the first KiB of a video
+  /// let header = camera
+  ///   .fs()
+  ///   .read_range("/store_00010001/DCIM/100CANON", "MVI_0001.MOV", FileType::Normal, 0, vec![0; 1024])
+  ///   .wait()?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn read_range(
+    &self,
+    folder: &str,
+    file: &str,
+    file_type: FileType,
+    offset: u64,
+    mut buffer: Vec<u8>,
+  ) -> Task<Result<Vec<u8>>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+
+    unsafe {
+      Task::new(move || {
+        let size = read_file_part(camera, context, &folder, &file, file_type, offset, &mut buffer)?;
+        buffer.truncate(size);
+
+        Ok(buffer)
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Download a file into a reusable buffer
+  ///
+  /// The size of the file is queried first, the buffer is resized to it and the data is read
+  /// directly into the buffer. Passing the returned buffer to the next call avoids allocating
+  /// and copying for every file, e.g. when ingesting many files of the same size.
+  ///
+  /// Drivers which can't read parts of files fall back to downloading the file into memory
+  /// and copying it into the buffer.
+  ///
+  /// ```no_run
+  /// use gphoto2::{Context, Result};
+  ///
+  /// # fn main() -> Result<()> {
+  /// let camera = Context::new()?.autodetect_camera().wait()?;
+  /// let mut buffer = Vec::new();
+  ///
+  /// for name in ["IMG_0001.JPG", "IMG_0002.JPG"] {
+  ///   buffer = camera.fs().download_into("/store_00010001/DCIM/100CANON", name, buffer).wait()?;
+  ///   println!("{} has {} bytes", name, buffer.len());
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn download_into(
+    &self,
+    folder: &str,
+    file: &str,
+    mut buffer: Vec<u8>,
+  ) -> Task<Result<Vec<u8>>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+
+    unsafe {
+      Task::new(move || {
+        let mut info = UninitBox::uninit();
+
+        try_gp_internal!(gp_camera_file_get_info(
+          *camera,
+          to_c_string!(folder),
+          to_c_string!(file),
+          info.as_mut_ptr(),
+          *context
+        )?);
+
+        let info = FileInfo::from_inner(info.assume_init());
+        let size: usize = info
+          .file()
+          .size()
+          .ok_or_else(|| Error::not_supported("The camera doesn't report the size of the file"))?
+          .try_into()?;
+
+        buffer.clear();
+        buffer.resize(size, 0);
+
+        match read_file_into(camera, context, &folder, &file, &mut buffer) {
+          Ok(read) => buffer.truncate(read),
+          Err(error) if error.is_not_supported() => {
+            let camera_file = CameraFile::new()?;
+
+            try_gp_internal!(gp_camera_file_get(
+              *camera,
+              to_c_string!(folder),
+              to_c_string!(file),
+              FileType::Normal.into(),
+              *camera_file.inner,
+              *context
+            )?);
+
+            buffer.clear();
+            buffer.extend_from_slice(camera_file.data()?);
+          }
+          Err(error) => return Err(error),
+        }
+
+        Ok(buffer)
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Read a file into a memory map, see [`CameraFilePath::download_mmap`](crate::file::CameraFilePath::download_mmap)
+  #[cfg(feature = "mmap")]
+  pub(crate) fn read_into_map(
+    &self,
+    folder: &str,
+    file: &str,
+    mut map: memmap2::MmapMut,
+  ) -> Task<Result<(memmap2::MmapMut, usize)>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let (folder, file) = (folder.to_owned(), file.to_owned());
+
+    unsafe {
+      Task::new(move || {
+        let read = read_file_into(camera, context, &folder, &file, &mut map)?;
+
+        Ok((map, read))
+      })
+    }
+    .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Read a file in chunks of `chunk_size` bytes
+  ///
+  /// See [`FileChunks`] for details. Returns an error of kind
+  /// [`ErrorKind::BadParameters`](crate::error::ErrorKind::BadParameters) if `chunk_size` is 0.
+  pub fn read_chunks(
+    &self,
+    folder: &str,
+    file: &str,
+    file_type: FileType,
+    chunk_size: usize,
+  ) -> Result<FileChunks> {
+    if chunk_size == 0 {
+      return Err(Error::bad_parameters("The chunk size must not be 0"));
+    }
+
+    Ok(FileChunks {
+      camera: self.camera.clone(),
+      folder: folder.to_owned(),
+      file: file.to_owned(),
+      file_type,
+      chunk_size,
+      cursor: Arc::default(),
+      finished: false,
+      #[cfg(feature = "futures")]
+      pending: None,
+    })
+  }
+
   /// Upload a file to the camera
   #[allow(clippy::boxed_local)]
   pub fn upload_file(&self, folder: &str, filename: &str, data: Box<[u8]>) -> Task<Result<()>> {
@@ -341,14 +1010,19 @@ impl<'a> CameraFS<'a> {
 
     unsafe {
       Task::new(move || {
-        try_gp_internal!(gp_file_new(&out file)?);
-        try_gp_internal!(gp_file_append(file, data.as_ptr().cast(), data.len().try_into()?)?);
+        let file = CameraFile::new()?;
+
+        try_gp_internal!(gp_file_append(
+          *file.inner,
+          data.as_ptr().cast(),
+          data.len().try_into()?
+        )?);
         try_gp_internal!(gp_camera_folder_put_file(
           *camera,
           to_c_string!(folder),
           to_c_string!(filename),
           FileType::Normal.into(),
-          file,
+          *file.inner,
           *context
         )?);
 
@@ -356,6 +1030,37 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
+  }
+
+  /// Upload the content of a reader to the camera
+  ///
+  /// The reader is read to the end on a separate thread before the upload is queued,
+  /// so a slow reader doesn't stall the libgphoto2 thread.
+  pub fn upload_from_reader<R>(
+    &self,
+    folder: &str,
+    filename: &str,
+    mut reader: R,
+  ) -> Task<Result<()>>
+  where
+    R: Read + Send + 'static,
+  {
+    self.upload_with(folder, filename, move || {
+      let mut data = Vec::new();
+      reader.read_to_end(&mut data)?;
+
+      Ok(data.into_boxed_slice())
+    })
+  }
+
+  /// Upload a file from the host filesystem to the camera
+  ///
+  /// The file is read on a separate thread before the upload is queued.
+  pub fn upload_from_path(&self, folder: &str, filename: &str, path: &Path) -> Task<Result<()>> {
+    let path = path.to_owned();
+
+    self.upload_with(folder, filename, move || Ok(fs::read(path)?.into_boxed_slice()))
   }
 
   /// Delete all files in a folder
@@ -371,6 +1076,7 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// List files in a folder
@@ -395,6 +1101,7 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// List folders in a folder
@@ -419,6 +1126,7 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// Creates a new folder
@@ -440,6 +1148,7 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
   }
 
   /// Removes a folder
@@ -461,11 +1170,118 @@ impl<'a> CameraFS<'a> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
+  }
+}
+
+/// Reads a part of a file into `buffer`, returns the number of bytes read
+///
+/// Must be called from a [`Task`].
+fn read_file_part(
+  camera: BackgroundPtr<libgphoto2_sys::Camera>,
+  context: BackgroundPtr<libgphoto2_sys::GPContext>,
+  folder: &str,
+  file: &str,
+  file_type: FileType,
+  offset: u64,
+  buffer: &mut [u8],
+) -> Result<usize> {
+  let mut size: u64 = buffer.len().try_into()?;
+
+  try_gp_internal!(gp_camera_file_read(
+    *camera,
+    to_c_string!(folder),
+    to_c_string!(file),
+    file_type.into(),
+    offset,
+    buffer.as_mut_ptr().cast(),
+    &mut size,
+    *context
+  )?);
+
+  Ok(size.try_into()?)
+}
+
+/// Stream a file into `sink` with `gp_file_new_from_handler` and flush it
+fn download_into_sink<S: FileSink>(
+  camera: BackgroundPtr<libgphoto2_sys::Camera>,
+  context: BackgroundPtr<libgphoto2_sys::GPContext>,
+  folder: &str,
+  file: &str,
+  sink: S,
+) -> Result<S> {
+  let mut handler = SinkHandler::new(sink);
+
+  handler.with_file(|camera_file| {
+    try_gp_internal!(gp_camera_file_get(
+      *camera,
+      to_c_string!(folder),
+      to_c_string!(file),
+      FileType::Normal.into(),
+      *camera_file.inner,
+      *context
+    )?);
+
+    Ok(())
+  })?;
+
+  let mut sink = handler.into_sink();
+  sink.flush()?;
+
+  Ok(sink)
+}
+
+/// Read a normal file from its start into `buffer` until the buffer is full or the file ended,
+/// returns the number of bytes read
+///
+/// Must be called from a [`Task`].
+fn read_file_into(
+  camera: BackgroundPtr<libgphoto2_sys::Camera>,
+  context: BackgroundPtr<libgphoto2_sys::GPContext>,
+  folder: &str,
+  file: &str,
+  buffer: &mut [u8],
+) -> Result<usize> {
+  let mut read = 0;
+
+  while read < buffer.len() {
+    let offset = read.try_into()?;
+
+    match read_file_part(
+      camera,
+      context,
+      folder,
+      file,
+      FileType::Normal,
+      offset,
+      &mut buffer[read..],
+    )? {
+      0 => break,
+      part_size => read += part_size,
+    }
   }
+
+  Ok(read)
 }
 
 /// Private implementations
 impl CameraFS<'_> {
+  /// Read the data on a separate thread, then upload it with [`CameraFS::upload_file`]
+  fn upload_with(
+    &self,
+    folder: &str,
+    filename: &str,
+    data: impl FnOnce() -> Result<Box<[u8]>> + Send + 'static,
+  ) -> Task<Result<()>> {
+    let camera = self.camera.clone();
+    let (folder, filename) = (folder.to_owned(), filename.to_owned());
+
+    Task::blocking(move |cancel| {
+      let data = data()?;
+      camera.fs().upload_file(&folder, &filename, data).wait_with(cancel)
+    })
+  }
+
   fn to_camera_file(
     &self,
     folder: &str,
@@ -506,5 +1322,122 @@ impl CameraFS<'_> {
       })
     }
     .context(context)
+    .for_camera(self.camera)
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::FilePermissions;
+  use libgphoto2_sys::CameraFileInfoFields as Fields;
+
+  #[test]
+  fn test_set_file_info() {
+    let camera = crate::sample_camera();
+    let fs = camera.fs();
+
+    let mut info = fs.file_info("/store_00010001/foo", "blank.jpg").wait().unwrap();
+    assert_eq!(info.changes().file.fields, Fields::GP_FILE_INFO_NONE);
+
+    // Keep the current values, the virtual camera doesn't support changing them
+    info.set_permissions(FilePermissions::new(true, true));
+    info.set_mtime(info.file().mtime().unwrap());
+
+    let changes = info.changes();
+    assert_eq!(changes.file.fields, Fields::GP_FILE_INFO_PERMISSIONS | Fields::GP_FILE_INFO_MTIME);
+    assert_eq!(changes.preview.fields, Fields::GP_FILE_INFO_NONE);
+    assert_eq!(changes.audio.fields, Fields::GP_FILE_INFO_NONE);
+
+    // Sending back the read-only size and mime type would fail with bad parameters
+    fs.set_file_info("/store_00010001/foo", "blank.jpg", &info).wait().unwrap();
+  }
+
+  #[test]
+  fn test_read_chunks() {
+    use crate::file::FileType;
+    use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+
+    let camera = crate::sample_camera();
+    let fs = camera.fs();
+
+    let chunks = fs.read_chunks("/store_00010001/foo", "blank.jpg", FileType::Normal, 100);
+    let chunks = chunks.unwrap().collect::<crate::Result<Vec<_>>>().unwrap();
+    assert_eq!(chunks.len(), SAMPLE_IMAGE.len().div_ceil(100));
+    assert_eq!(chunks.concat(), SAMPLE_IMAGE);
+
+    let mut missing =
+      fs.read_chunks("/store_00010001/foo", "missing.jpg", FileType::Normal, 100).unwrap();
+    assert!(matches!(missing.next(), Some(Err(_))));
+    assert!(missing.next().is_none());
+
+    let error = fs.read_chunks("/store_00010001/foo", "blank.jpg", FileType::Normal, 0);
+    assert_eq!(error.err().unwrap().kind(), crate::error::ErrorKind::BadParameters);
+  }
+
+  #[test]
+  fn test_read_chunks_out_of_order() {
+    use crate::file::FileType;
+    use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+
+    let camera = crate::sample_camera();
+    let mut chunks =
+      camera.fs().read_chunks("/store_00010001/foo", "blank.jpg", FileType::Normal, 100).unwrap();
+
+    let (first, second) = (chunks.next_chunk(), chunks.next_chunk());
+    let second = second.wait().unwrap().unwrap();
+    let first = first.wait().unwrap().unwrap();
+
+    assert_eq!(first, SAMPLE_IMAGE[..100]);
+    assert_eq!(second, SAMPLE_IMAGE[100..200]);
+  }
+
+  #[cfg(feature = "futures")]
+  #[test]
+  fn test_read_chunks_stream() {
+    use crate::file::FileType;
+    use futures_core::Stream;
+    use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+    use std::{
+      pin::Pin,
+      sync::Arc,
+      task::{Context, Poll, Wake, Waker},
+      thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+      fn wake(self: Arc<Self>) {
+        self.0.unpark();
+      }
+    }
+
+    fn block_on_next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+      let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+      let mut cx = Context::from_waker(&waker);
+
+      loop {
+        match Pin::new(&mut *stream).poll_next(&mut cx) {
+          Poll::Ready(item) => return item,
+          Poll::Pending => thread::park(),
+        }
+      }
+    }
+
+    let camera = crate::sample_camera();
+    let fs = camera.fs();
+
+    let mut chunks =
+      fs.read_chunks("/store_00010001/foo", "blank.jpg", FileType::Normal, 100).unwrap();
+    let mut data = Vec::new();
+    while let Some(chunk) = block_on_next(&mut chunks) {
+      data.extend(chunk.unwrap());
+    }
+    assert_eq!(data, SAMPLE_IMAGE);
+
+    let mut missing =
+      fs.read_chunks("/store_00010001/foo", "missing.jpg", FileType::Normal, 100).unwrap();
+    assert!(matches!(block_on_next(&mut missing), Some(Err(_))));
+    assert!(block_on_next(&mut missing).is_none());
   }
 }
@@ -0,0 +1,290 @@
+//! Manual focus control
+//!
+//! Drives the focus motor of the lens through the `manualfocusdrive` (or `manualfocus`)
+//! configuration, which most drivers only support while live view is active.
+//!
+//! ## Moving the focus
+//! ```no_run
+//! use gphoto2::{focus::FocusStep, Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//!
+//! camera.focus(FocusStep::NearLarge)?;
+//! camera.focus(FocusStep::FarSmall)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Focus stacking
+//! ```no_run
+//! use gphoto2::{focus::FocusStack, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let camera = context.autodetect_camera().wait()?;
+//!
+//! let files = FocusStack::new(&camera)
+//!   .frames(20)
+//!   .step(2)
+//!   .settle_time(Duration::from_secs(1))
+//!   .run()?;
+//!
+//! for file in files {
+//!   println!("Captured {}", file.name());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  file::CameraFilePath,
+  widget::{RadioWidget, ToggleWidget, Widget},
+  Camera, Error, Result,
+};
+use std::{thread, time::Duration};
+
+/// Direction and size of a manual focus step
+///
+/// Drivers use different value schemes for their focus drive, this enum maps onto them:
+///  - Choices like `Near 1` .. `Far 3` (e.g. Canon) use the level 1, 2 or 3
+///  - Small ranges like `-7..=7` (e.g. Sony) use a third, two thirds or all of the range
+///  - Large ranges like `-32767..=32767` (e.g. Nikon) use 10, 100 or 1000 motor steps
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FocusStep {
+  /// Small step towards near
+  NearSmall,
+  /// Medium step towards near
+  NearMedium,
+  /// Large step towards near
+  NearLarge,
+  /// Small step towards far
+  FarSmall,
+  /// Medium step towards far
+  FarMedium,
+  /// Large step towards far
+  FarLarge,
+}
+
+impl FocusStep {
+  fn is_near(self) -> bool {
+    matches!(self, Self::NearSmall | Self::NearMedium | Self::NearLarge)
+  }
+
+  fn level(self) -> u8 {
+    match self {
+      Self::NearSmall | Self::FarSmall => 1,
+      Self::NearMedium | Self::FarMedium => 2,
+      Self::NearLarge | Self::FarLarge => 3,
+    }
+  }
+
+  /// Amount of a step for a range widget whose values go up to `max` in both directions
+  fn range_amount(self, max: f32) -> f32 {
+    let level = f32::from(self.level());
+
+    if max > 100.0 {
+      10f32.powf(level).min(max)
+    } else {
+      (max * level / 3.0).round().max(1.0)
+    }
+  }
+}
+
+fn focus_widget(camera: &Camera) -> Result<Widget> {
+  camera
+    .config_key::<Widget>("manualfocusdrive")
+    .wait()
+    .or_else(|_| camera.config_key::<Widget>("manualfocus").wait())
+}
+
+/// Select the `Near n` / `Far n` choice closest to `level` (but not above if possible)
+fn select_radio_step(radio: &RadioWidget, near: bool, level: u16) -> Result<()> {
+  let choice = closest_step(radio.choices_iter(), near, level).ok_or_else(|| {
+    let direction = if near { "Near" } else { "Far" };
+    Error::not_supported(format!("{} has no {direction} choices", radio.name()))
+  })?;
+
+  radio.set_choice(&choice)
+}
+
+/// The `Near n` / `Far n` choice closest to `level` (but not above if possible)
+fn closest_step(
+  choices: impl IntoIterator<Item = String>,
+  near: bool,
+  level: u16,
+) -> Option<String> {
+  let direction = if near { "Near" } else { "Far" };
+
+  let choices: Vec<(u16, String)> = choices
+    .into_iter()
+    .filter_map(|choice| {
+      let choice_level = choice.strip_prefix(direction)?.trim().parse().ok()?;
+      Some((choice_level, choice))
+    })
+    .collect();
+
+  choices
+    .iter()
+    .filter(|(choice_level, _)| *choice_level <= level)
+    .max_by_key(|(choice_level, _)| *choice_level)
+    .or_else(|| choices.iter().min_by_key(|(choice_level, _)| *choice_level))
+    .map(|(_, choice)| choice.clone())
+}
+
+/// Drive the focus by a [`FocusStep`]
+pub(crate) fn drive_focus_step(camera: &Camera, step: FocusStep) -> Result<()> {
+  let widget = focus_widget(camera)?;
+
+  match &widget {
+    Widget::Range(range_widget) => {
+      let (range, _) = range_widget.range_and_step();
+      let amount = step.range_amount(range.start().abs().min(range.end().abs()));
+
+      range_widget.try_set_value(if step.is_near() { -amount } else { amount })?;
+    }
+    Widget::Radio(radio) => select_radio_step(radio, step.is_near(), u16::from(step.level()))?,
+    _ => return Err(Error::not_supported(format!("Unsupported focus drive widget: {widget:?}"))),
+  }
+
+  camera.set_config(&widget).wait()
+}
+
+/// Drive the focus by a signed amount, negative values move towards near, positive towards far
+///
+/// For range widgets (e.g. Nikon) the amount is written as is, clamped to the range of the widget.
+/// For radio widgets (e.g. Canon) the amount selects the `Near n` / `Far n` choice,
+/// where `n` is the absolute amount clamped to the available levels.
+pub(crate) fn drive_focus(camera: &Camera, amount: i16) -> Result<()> {
+  if amount == 0 {
+    return Ok(());
+  }
+
+  let widget = focus_widget(camera)?;
+
+  match &widget {
+    Widget::Range(range_widget) => {
+      let (range, _) = range_widget.range_and_step();
+      range_widget.try_set_value(f32::from(amount).clamp(*range.start(), *range.end()))?;
+    }
+    Widget::Radio(radio) => select_radio_step(radio, amount < 0, amount.unsigned_abs())?,
+    _ => return Err(Error::not_supported(format!("Unsupported focus drive widget: {widget:?}"))),
+  }
+
+  camera.set_config(&widget).wait()
+}
+
+/// Captures a series of images while moving the focus between them
+///
+/// Before every capture except the first, the focus is moved by the configured step,
+/// see [`FocusStack::step`]. If the camera has a `viewfinder` configuration, live view is
+/// enabled for the duration of the stack, since most drivers can only drive the focus in live view.
+pub struct FocusStack {
+  camera: Camera,
+  frames: usize,
+  step: i16,
+  settle_time: Duration,
+}
+
+impl FocusStack {
+  /// Create a focus stack of 10 frames, moving the focus towards far by a step of 1
+  pub fn new(camera: &Camera) -> Self {
+    Self { camera: camera.clone(), frames: 10, step: 1, settle_time: Duration::from_millis(500) }
+  }
+
+  /// Set the number of frames to capture
+  pub fn frames(mut self, frames: usize) -> Self {
+    self.frames = frames;
+    self
+  }
+
+  /// Set the focus step between two frames
+  ///
+  /// Negative steps move the focus towards near, positive steps towards far.
+  /// On cameras with a range `manualfocusdrive` (e.g. Nikon) the step is in driver units,
+  /// on cameras with choices like `Near 1` .. `Far 3` (e.g. Canon) it selects the step size.
+  pub fn step(mut self, step: i16) -> Self {
+    self.step = step;
+    self
+  }
+
+  /// Set the time to wait after moving the focus before capturing
+  pub fn settle_time(mut self, settle_time: Duration) -> Self {
+    self.settle_time = settle_time;
+    self
+  }
+
+  /// Capture the stack, returns the captured files in order
+  pub fn run(&self) -> Result<Vec<CameraFilePath>> {
+    let viewfinder = self.camera.config_key::<ToggleWidget>("viewfinder").wait().ok();
+
+    if let Some(viewfinder) = &viewfinder {
+      viewfinder.try_set_toggled(true)?;
+      self.camera.set_config(viewfinder).wait()?;
+    }
+
+    let result = self.capture_frames();
+
+    if let Some(viewfinder) = &viewfinder {
+      viewfinder.set_toggled(false);
+
+      if let Err(error) = self.camera.set_config(viewfinder).wait() {
+        log::warn!("Could not disable viewfinder: {}", error);
+      }
+    }
+
+    result
+  }
+
+  fn capture_frames(&self) -> Result<Vec<CameraFilePath>> {
+    let mut files = Vec::with_capacity(self.frames);
+
+    for frame in 0..self.frames {
+      if frame > 0 {
+        drive_focus(&self.camera, self.step)?;
+        thread::sleep(self.settle_time);
+      }
+
+      files.push(self.camera.capture_image().wait()?);
+    }
+
+    Ok(files)
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{closest_step, FocusStep};
+
+  #[test]
+  fn test_range_amount() {
+    // Small ranges use thirds of the range
+    assert_eq!(FocusStep::NearSmall.range_amount(7.0), 2.0);
+    assert_eq!(FocusStep::FarMedium.range_amount(7.0), 5.0);
+    assert_eq!(FocusStep::NearLarge.range_amount(7.0), 7.0);
+    assert_eq!(FocusStep::FarSmall.range_amount(1.0), 1.0);
+
+    // Large ranges use motor steps
+    assert_eq!(FocusStep::NearSmall.range_amount(32767.0), 10.0);
+    assert_eq!(FocusStep::FarMedium.range_amount(32767.0), 100.0);
+    assert_eq!(FocusStep::FarLarge.range_amount(32767.0), 1000.0);
+    assert_eq!(FocusStep::FarLarge.range_amount(500.0), 500.0);
+  }
+
+  #[test]
+  fn test_closest_step() {
+    let canon =
+      || ["None", "Near 1", "Near 2", "Near 3", "Far 1", "Far 2", "Far 3"].map(String::from);
+
+    assert_eq!(closest_step(canon(), true, 2).as_deref(), Some("Near 2"));
+    assert_eq!(closest_step(canon(), false, 1).as_deref(), Some("Far 1"));
+    assert_eq!(closest_step(canon(), false, 100).as_deref(), Some("Far 3"));
+
+    // Fall back to the smallest step if all are larger
+    let large_only = || ["Near 2", "Near 3"].map(String::from);
+    assert_eq!(closest_step(large_only(), true, 1).as_deref(), Some("Near 2"));
+    assert_eq!(closest_step(large_only(), false, 1), None);
+  }
+}
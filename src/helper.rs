@@ -1,13 +1,15 @@
+use crate::{Error, Result};
 use std::{
   borrow::Cow,
   ffi,
   fs::File,
   mem::MaybeUninit,
   os::raw::{c_char, c_int},
-  sync::Once,
+  path::Path,
 };
 
-static HOOK_LOG_FUNCTION: Once = Once::new();
+#[cfg(not(feature = "extended_logs"))]
+static HOOK_LOG_FUNCTION: std::sync::Once = std::sync::Once::new();
 
 pub fn char_slice_to_cow(chars: &[c_char]) -> Cow<'_, str> {
   unsafe { String::from_utf8_lossy(ffi::CStr::from_ptr(chars.as_ptr()).to_bytes()) }
@@ -41,40 +43,64 @@ impl IntoUnixFd for File {
   }
 }
 
-#[cfg(feature = "extended_logs")]
-pub fn hook_gp_log() {
-  use libgphoto2_sys::GPLogLevel;
-  use log::LevelFilter;
+/// Forward a libgphoto2 log message to `tracing`
+#[cfg(feature = "tracing")]
+pub(crate) fn log_message(level: log::Level, domain: Option<&str>, message: &str) {
+  let domain = domain.unwrap_or("context");
 
-  unsafe extern "C" fn log_function(
-    level: libgphoto2_sys::GPLogLevel,
-    domain: *const std::os::raw::c_char,
-    message: *const std::os::raw::c_char,
-    _data: *mut ffi::c_void,
-  ) {
-    let log_level = match level {
-      GPLogLevel::GP_LOG_ERROR => log::Level::Error,
-      GPLogLevel::GP_LOG_DEBUG => log::Level::Debug,
-      GPLogLevel::GP_LOG_VERBOSE => log::Level::Info,
-      GPLogLevel::GP_LOG_DATA => log::Level::Trace,
+  macro_rules! event {
+    ($level:expr) => {
+      tracing::event!(target: "gphoto2", $level, domain, "{}", message)
     };
+  }
 
-    let target = format!("gphoto2::{}", chars_to_string(domain));
+  match level {
+    log::Level::Error => event!(tracing::Level::ERROR),
+    log::Level::Warn => event!(tracing::Level::WARN),
+    log::Level::Info => event!(tracing::Level::INFO),
+    log::Level::Debug => event!(tracing::Level::DEBUG),
+    log::Level::Trace => event!(tracing::Level::TRACE),
+  }
+}
 
-    log::log!(target: &target, log_level, "{}", chars_to_string(message));
+/// Forward a libgphoto2 log message to `log`
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn log_message(level: log::Level, domain: Option<&str>, message: &str) {
+  match domain {
+    Some(domain) => log::log!(target: &format!("gphoto2::{domain}"), level, "{}", message),
+    None => log::log!(target: "gphoto2", level, "{}", message),
   }
+}
 
-  let max_log_level = match log::STATIC_MAX_LEVEL {
-    LevelFilter::Debug | LevelFilter::Warn => GPLogLevel::GP_LOG_DEBUG,
-    LevelFilter::Error => GPLogLevel::GP_LOG_ERROR,
-    LevelFilter::Info => GPLogLevel::GP_LOG_VERBOSE,
-    LevelFilter::Trace => GPLogLevel::GP_LOG_DATA,
-    LevelFilter::Off => return,
-  };
+/// Maximum level of `tracing` events which are enabled at compile time
+#[cfg(feature = "tracing")]
+pub(crate) fn static_max_level() -> log::LevelFilter {
+  use tracing::level_filters::{LevelFilter, STATIC_MAX_LEVEL};
+
+  match STATIC_MAX_LEVEL {
+    LevelFilter::OFF => log::LevelFilter::Off,
+    LevelFilter::ERROR => log::LevelFilter::Error,
+    LevelFilter::WARN => log::LevelFilter::Warn,
+    LevelFilter::INFO => log::LevelFilter::Info,
+    LevelFilter::DEBUG => log::LevelFilter::Debug,
+    _ => log::LevelFilter::Trace,
+  }
+}
 
-  HOOK_LOG_FUNCTION.call_once(|| unsafe {
-    libgphoto2_sys::gp_log_add_func(max_log_level, Some(log_function), std::ptr::null_mut());
-  });
+/// Maximum level of `log` messages which are enabled at compile time
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn static_max_level() -> log::LevelFilter {
+  log::STATIC_MAX_LEVEL
+}
+
+/// Check if messages of a level would be logged
+#[cfg(not(feature = "extended_logs"))]
+fn log_enabled(level: log::Level) -> bool {
+  if cfg!(feature = "tracing") {
+    level <= static_max_level()
+  } else {
+    log::log_enabled!(level)
+  }
 }
 
 #[cfg(not(feature = "extended_logs"))]
@@ -88,11 +114,11 @@ pub fn hook_gp_context_log_func(context: *mut libgphoto2_sys::GPContext) {
   ) {
     let log_level: Level = std::mem::transmute(log_level);
 
-    log::log!(target: "gphoto2", log_level, "{}", chars_to_string(message));
+    log_message(log_level, None, &chars_to_string(message));
   }
 
   HOOK_LOG_FUNCTION.call_once(|| unsafe {
-    if log::log_enabled!(log::Level::Error) {
+    if log_enabled(log::Level::Error) {
       let log_level_as_ptr = std::mem::transmute(log::Level::Error);
 
       libgphoto2_sys::gp_context_set_error_func(context, Some(log_func), log_level_as_ptr);
@@ -101,7 +127,7 @@ pub fn hook_gp_context_log_func(context: *mut libgphoto2_sys::GPContext) {
       libgphoto2_sys::gp_context_set_message_func(context, Some(log_func), log_level_as_ptr);
     }
 
-    if log::log_enabled!(log::Level::Info) {
+    if log_enabled(log::Level::Info) {
       libgphoto2_sys::gp_context_set_status_func(
         context,
         Some(log_func),
@@ -111,6 +137,92 @@ pub fn hook_gp_context_log_func(context: *mut libgphoto2_sys::GPContext) {
   });
 }
 
+/// Run `f` with an environment variable read by libgphoto2 set to `value` and restore it afterwards
+///
+/// Only for settings libgphoto2 has no API for. The variable is set for the whole process while
+/// `f` runs, so this must be called from a [`Task`](crate::task::Task) to keep libgphoto2 from
+/// reading the environment while it changes.
+pub(crate) fn with_libgphoto2_env<T>(
+  name: &str,
+  value: &Path,
+  f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+  if value.to_str().is_none() {
+    return Err(Error::bad_parameters(format!("{name} {} is not valid UTF-8", value.display())));
+  }
+
+  let previous = libgphoto2_env(name)?;
+
+  set_libgphoto2_env(name, Some(value.as_os_str()))?;
+  let result = f();
+
+  if let Err(error) = set_libgphoto2_env(name, previous.as_deref()) {
+    log::warn!("Could not restore {}: {}", name, error);
+  }
+
+  result
+}
+
+/// Read a variable from the environment seen by libgphoto2
+#[cfg(not(windows))]
+fn libgphoto2_env(name: &str) -> Result<Option<ffi::OsString>> {
+  Ok(std::env::var_os(name))
+}
+
+/// Read a variable from the environment seen by libgphoto2
+///
+/// The C runtime keeps its own copy of the environment on Windows.
+#[cfg(windows)]
+fn libgphoto2_env(name: &str) -> Result<Option<ffi::OsString>> {
+  let name = ffi::CString::new(name)?;
+  let value = unsafe { libc::getenv(name.as_ptr()) };
+
+  if value.is_null() {
+    return Ok(None);
+  }
+
+  Ok(Some(unsafe { ffi::CStr::from_ptr(value) }.to_string_lossy().into_owned().into()))
+}
+
+/// Set or remove a variable in the environment seen by libgphoto2
+///
+/// `std::env` goes through the same `environ` libgphoto2 reads with `getenv` and takes the
+/// lock of the standard library, so other threads using `std::env` don't race with it.
+#[cfg(not(windows))]
+fn set_libgphoto2_env(name: &str, value: Option<&ffi::OsStr>) -> Result<()> {
+  match value {
+    Some(value) => std::env::set_var(name, value),
+    None => std::env::remove_var(name),
+  }
+
+  Ok(())
+}
+
+/// Set or remove a variable in the environment seen by libgphoto2
+///
+/// `std::env::set_var` doesn't update the copy of the C runtime read by libgphoto2 on Windows.
+#[cfg(windows)]
+fn set_libgphoto2_env(name: &str, value: Option<&ffi::OsStr>) -> Result<()> {
+  extern "C" {
+    fn _putenv_s(name: *const c_char, value: *const c_char) -> c_int;
+  }
+
+  let value = value
+    .map(|value| {
+      value.to_str().ok_or_else(|| Error::bad_parameters(format!("{name} is not valid UTF-8")))
+    })
+    .transpose()?;
+
+  // An empty value removes the variable
+  let (name, value) = (ffi::CString::new(name)?, ffi::CString::new(value.unwrap_or_default())?);
+
+  if unsafe { _putenv_s(name.as_ptr(), value.as_ptr()) } != 0 {
+    return Err(std::io::Error::last_os_error().into());
+  }
+
+  Ok(())
+}
+
 pub struct UninitBox<T> {
   inner: Box<MaybeUninit<T>>,
 }
@@ -194,6 +306,20 @@ macro_rules! bitflags {
           .finish()
       }
     }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for $name {
+      fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let fields: &[&str] = &[$(stringify!($field)),*];
+        let mut s = serializer.serialize_struct(stringify!($name), fields.len())?;
+        $(
+          s.serialize_field(stringify!($field), &self.$field())?;
+        )*
+        s.end()
+      }
+    }
   };
 }
 
@@ -5,16 +5,33 @@
 
 pub mod abilities;
 pub mod camera;
+pub mod config;
 pub mod context;
+pub mod download;
 pub mod error;
 pub mod file;
 pub mod filesys;
+pub mod focus;
 pub(crate) mod helper;
 pub mod list;
+#[cfg(feature = "extended_logs")]
+pub mod logging;
+pub mod mock;
+pub mod monitor;
+pub mod pool;
 pub mod port;
+#[cfg(feature = "presets")]
+pub mod presets;
+pub mod preview;
+pub mod retry;
+pub mod settings;
 pub mod task;
+pub mod tether;
 pub(crate) mod thread;
+pub mod timelapse;
 pub mod widget;
+pub(crate) mod worker;
+pub mod zoom;
 
 use std::ffi::CStr;
 
@@ -46,6 +63,62 @@ pub fn library_version() -> Option<&'static str> {
   }
 }
 
+/// Detailed version information of a library
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionInfo {
+  /// Version, e.g. `2.5.31`
+  pub version: String,
+  /// Build details and enabled features, e.g. the compiler and optional dependencies
+  pub details: Vec<String>,
+}
+
+impl VersionInfo {
+  /// Read a NULL terminated list of strings where the first item is the version
+  unsafe fn from_list(mut list: *const *const std::os::raw::c_char) -> Self {
+    let mut strings = Vec::new();
+
+    while !list.is_null() && !(*list).is_null() {
+      strings.push(CStr::from_ptr(*list).to_string_lossy().into_owned());
+      list = list.add(1);
+    }
+
+    let mut strings = strings.into_iter();
+    Self { version: strings.next().unwrap_or_default(), details: strings.collect() }
+  }
+
+  /// Numeric major, minor and patch version, ignoring suffixes like `-dev`
+  pub fn numeric(&self) -> Option<(u32, u32, u32)> {
+    let mut parts = self.version.split(|c: char| !c.is_ascii_digit());
+    let mut next = || parts.next()?.parse().ok();
+
+    Some((next()?, next()?, next().unwrap_or(0)))
+  }
+
+  /// Check if the version is at least `major.minor.patch`
+  pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+    self.numeric().is_some_and(|version| version >= (major, minor, patch))
+  }
+}
+
+/// Get the version and build details of the libgphoto2 library used
+pub fn library_version_info() -> VersionInfo {
+  unsafe {
+    VersionInfo::from_list(libgphoto2_sys::gp_library_version(
+      libgphoto2_sys::GPVersionVerbosity::GP_VERSION_VERBOSE,
+    ))
+  }
+}
+
+/// Get the version and build details of the libgphoto2_port library used
+pub fn port_library_version_info() -> VersionInfo {
+  unsafe {
+    VersionInfo::from_list(libgphoto2_sys::gp_port_library_version(
+      libgphoto2_sys::GPVersionVerbosity::GP_VERSION_VERBOSE,
+    ))
+  }
+}
+
 #[cfg(all(test, feature = "test"))]
 fn sample_context() -> Context {
   use std::sync::Once;
@@ -67,8 +140,61 @@ fn sample_context() -> Context {
   Context::new().unwrap()
 }
 
+#[cfg(all(test, feature = "test"))]
+fn sample_camera() -> Camera {
+  sample_context().autodetect_camera().wait().unwrap()
+}
+
+/// Camera for tests changing the `capturetarget` configuration
+///
+/// The ptp2 driver stores the capture target in the global libgphoto2 settings instead of the
+/// camera, so these tests are serialized and the default is restored when the guard is dropped.
+#[cfg(all(test, feature = "test"))]
+struct CaptureTargetGuard {
+  camera: Camera,
+  _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(all(test, feature = "test"))]
+fn lock_capture_target() -> CaptureTargetGuard {
+  use std::sync::Mutex;
+
+  static LOCK: Mutex<()> = Mutex::new(());
+
+  let lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+  CaptureTargetGuard { camera: sample_camera(), _lock: lock }
+}
+
+#[cfg(all(test, feature = "test"))]
+impl std::ops::Deref for CaptureTargetGuard {
+  type Target = Camera;
+
+  fn deref(&self) -> &Camera {
+    &self.camera
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+impl Drop for CaptureTargetGuard {
+  fn drop(&mut self) {
+    if let Err(error) = self.camera.apply_config([("capturetarget", "Internal RAM")]).wait() {
+      log::warn!("Could not restore the capture target: {}", error);
+    }
+  }
+}
+
 #[cfg(all(test, feature = "test"))]
 #[test]
 fn test_version() {
   insta::assert_snapshot!(library_version().unwrap());
 }
+
+#[cfg(all(test, feature = "test"))]
+#[test]
+fn test_version_info() {
+  let version = library_version_info();
+
+  assert_eq!(Some(version.version.as_str()), library_version());
+  assert!(version.at_least(2, 5, 10));
+  assert!(!port_library_version_info().version.is_empty());
+}
@@ -0,0 +1,170 @@
+//! Runtime control of the libgphoto2 log hook
+//!
+//! With the `extended_logs` feature, the logs of libgphoto2 are received with `gp_log_add_func`.
+//! By default the hook is installed when the first [`Context`](crate::Context) is created,
+//! at the most verbose level enabled at compile time. Debug logging slows down transfers
+//! considerably, so the level can be changed or the hook removed at any time.
+//!
+//! ## Changing the log level
+//! ```no_run
+//! use gphoto2::{logging::{self, LogLevel}, Result};
+//!
+//! # fn main() -> Result<()> {
+//! // Only log errors while downloading
+//! logging::set_log_level(Some(LogLevel::Error))?;
+//!
+//! // Log everything including PTP packet hexdumps while debugging
+//! logging::set_log_level(Some(LogLevel::Data))?;
+//!
+//! // Remove the hook
+//! logging::set_log_level(None)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  helper::{chars_to_string, log_message, static_max_level},
+  try_gp_internal, Result,
+};
+use libgphoto2_sys::GPLogLevel;
+use std::{
+  ffi,
+  os::raw::{c_char, c_int},
+  sync::Mutex,
+};
+
+/// Level of libgphoto2 log messages, each level includes the ones before
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+  /// Errors, logged at the `error` level
+  Error,
+  /// Verbose messages, logged at the `info` level
+  Verbose,
+  /// Debug messages, logged at the `debug` level
+  Debug,
+  /// Hexdumps of transferred data, logged at the `trace` level
+  Data,
+}
+
+impl From<LogLevel> for GPLogLevel {
+  fn from(level: LogLevel) -> Self {
+    match level {
+      LogLevel::Error => GPLogLevel::GP_LOG_ERROR,
+      LogLevel::Verbose => GPLogLevel::GP_LOG_VERBOSE,
+      LogLevel::Debug => GPLogLevel::GP_LOG_DEBUG,
+      LogLevel::Data => GPLogLevel::GP_LOG_DATA,
+    }
+  }
+}
+
+struct Hook {
+  /// If false, the default hook is installed by the first context
+  configured: bool,
+  id: Option<c_int>,
+  level: Option<LogLevel>,
+}
+
+static HOOK: Mutex<Hook> = Mutex::new(Hook { configured: false, id: None, level: None });
+
+unsafe extern "C" fn log_function(
+  level: GPLogLevel,
+  domain: *const c_char,
+  message: *const c_char,
+  _data: *mut ffi::c_void,
+) {
+  let log_level = match level {
+    GPLogLevel::GP_LOG_ERROR => log::Level::Error,
+    GPLogLevel::GP_LOG_DEBUG => log::Level::Debug,
+    GPLogLevel::GP_LOG_VERBOSE => log::Level::Info,
+    GPLogLevel::GP_LOG_DATA => log::Level::Trace,
+  };
+
+  log_message(log_level, Some(&chars_to_string(domain)), &chars_to_string(message));
+}
+
+impl Hook {
+  fn set_level(&mut self, level: Option<LogLevel>) -> Result<()> {
+    if let Some(id) = self.id.take() {
+      self.level = None;
+      try_gp_internal!(gp_log_remove_func(id)?);
+    }
+
+    if let Some(level) = level {
+      try_gp_internal!(let id = gp_log_add_func(level.into(), Some(log_function), std::ptr::null_mut())?);
+      self.id = Some(id);
+      self.level = Some(level);
+    }
+
+    Ok(())
+  }
+}
+
+/// Install the hook at the given level, replacing the current one, or remove it with `None`
+///
+/// After calling this, creating a [`Context`](crate::Context) no longer installs the default hook.
+pub fn set_log_level(level: Option<LogLevel>) -> Result<()> {
+  let mut hook = HOOK.lock().unwrap_or_else(|error| error.into_inner());
+  hook.configured = true;
+  hook.set_level(level)
+}
+
+/// Get the level the hook is installed at, `None` if it isn't installed
+pub fn log_level() -> Option<LogLevel> {
+  HOOK.lock().unwrap_or_else(|error| error.into_inner()).level
+}
+
+/// Install the hook at the most verbose level enabled at compile time,
+/// unless it was configured with [`set_log_level`]
+pub(crate) fn install_default() {
+  let mut hook = HOOK.lock().unwrap_or_else(|error| error.into_inner());
+
+  if hook.configured {
+    return;
+  }
+
+  hook.configured = true;
+
+  let level = match static_max_level() {
+    log::LevelFilter::Off => None,
+    log::LevelFilter::Error => Some(LogLevel::Error),
+    log::LevelFilter::Info => Some(LogLevel::Verbose),
+    log::LevelFilter::Warn | log::LevelFilter::Debug => Some(LogLevel::Debug),
+    log::LevelFilter::Trace => Some(LogLevel::Data),
+  };
+
+  if let Err(error) = hook.set_level(level) {
+    log::warn!("Could not install the libgphoto2 log hook: {}", error);
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{install_default, log_level, set_log_level, LogLevel};
+  use libgphoto2_sys::GPLogLevel;
+
+  #[test]
+  fn test_log_level_mapping() {
+    assert_eq!(GPLogLevel::from(LogLevel::Error), GPLogLevel::GP_LOG_ERROR);
+    assert_eq!(GPLogLevel::from(LogLevel::Verbose), GPLogLevel::GP_LOG_VERBOSE);
+    assert_eq!(GPLogLevel::from(LogLevel::Debug), GPLogLevel::GP_LOG_DEBUG);
+    assert_eq!(GPLogLevel::from(LogLevel::Data), GPLogLevel::GP_LOG_DATA);
+  }
+
+  #[test]
+  fn test_set_log_level() {
+    // Make sure the default hook doesn't replace the configured one later
+    install_default();
+    let default_level = log_level();
+
+    set_log_level(Some(LogLevel::Error)).unwrap();
+    assert_eq!(log_level(), Some(LogLevel::Error));
+
+    install_default();
+    assert_eq!(log_level(), Some(LogLevel::Error));
+
+    set_log_level(None).unwrap();
+    assert_eq!(log_level(), None);
+
+    set_log_level(default_level).unwrap();
+  }
+}
@@ -0,0 +1,410 @@
+//! Mocking cameras in tests
+//!
+//! Code written against the [`CameraOps`] trait instead of [`Camera`] can be tested
+//! with a [`MockCamera`], which returns scripted responses instead of talking to a camera.
+//! Calls to the mock are recorded, so tests can check what the code did.
+//!
+//! ## Testing a capture pipeline
+//! ```no_run
+//! use gphoto2::{
+//!   mock::{CameraOps, MockCall, MockCamera},
+//!   Result,
+//! };
+//!
+//! /// Captures an image and returns its data
+//! fn capture(camera: &impl CameraOps) -> Result<Vec<u8>> {
+//!   let path = camera.capture_image().wait()?;
+//!   let file = camera.download(&path.folder(), &path.name()).wait()?;
+//!
+//!   Ok(file.data()?.to_vec())
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! let camera = MockCamera::new("Mock DSLR");
+//! camera.push_capture("/store_00010001/DCIM", "IMG_0001.JPG", b"jpeg data".to_vec());
+//!
+//! assert_eq!(capture(&camera)?, b"jpeg data");
+//! assert_eq!(camera.calls()[0], MockCall::CaptureImage);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  camera::CameraEvent,
+  config::{ConfigKeyError, ConfigSnapshot},
+  file::{CameraFile, CameraFilePath},
+  task::Task,
+  Camera, Error, Result,
+};
+use std::{
+  collections::{BTreeMap, VecDeque},
+  sync::{Arc, Mutex, MutexGuard},
+  time::Duration,
+};
+
+/// Operations of a camera used for capturing and transferring images
+///
+/// Implemented by [`Camera`] and by [`MockCamera`] for tests. The methods behave
+/// like the methods of [`Camera`] with the same name.
+pub trait CameraOps {
+  /// Model name of the camera, see [`Abilities::model`](crate::abilities::Abilities::model)
+  fn model(&self) -> String;
+
+  /// See [`Camera::capture_image`]
+  fn capture_image(&self) -> Task<Result<CameraFilePath>>;
+
+  /// See [`Camera::trigger_capture`]
+  fn trigger_capture(&self) -> Task<Result<()>>;
+
+  /// See [`Camera::capture_preview`]
+  fn capture_preview(&self) -> Task<Result<CameraFile>>;
+
+  /// See [`Camera::wait_event`]
+  fn wait_event(&self, timeout: Duration) -> Task<Result<CameraEvent>>;
+
+  /// See [`CameraFS::download`](crate::filesys::CameraFS::download)
+  fn download(&self, folder: &str, name: &str) -> Task<Result<CameraFile>>;
+
+  /// See [`CameraFS::delete_file`](crate::filesys::CameraFS::delete_file)
+  fn delete_file(&self, folder: &str, name: &str) -> Task<Result<()>>;
+
+  /// See [`Camera::summary`]
+  fn summary(&self) -> Result<String>;
+
+  /// See [`Camera::config_snapshot`]
+  fn config_snapshot(&self) -> Task<Result<ConfigSnapshot>>;
+
+  /// See [`Camera::restore_snapshot`]
+  fn restore_snapshot(&self, snapshot: &ConfigSnapshot) -> Task<Result<Vec<ConfigKeyError>>>;
+}
+
+impl CameraOps for Camera {
+  fn model(&self) -> String {
+    self.abilities().model().into_owned()
+  }
+
+  fn capture_image(&self) -> Task<Result<CameraFilePath>> {
+    Camera::capture_image(self)
+  }
+
+  fn trigger_capture(&self) -> Task<Result<()>> {
+    Camera::trigger_capture(self)
+  }
+
+  fn capture_preview(&self) -> Task<Result<CameraFile>> {
+    Camera::capture_preview(self)
+  }
+
+  fn wait_event(&self, timeout: Duration) -> Task<Result<CameraEvent>> {
+    Camera::wait_event(self, timeout)
+  }
+
+  fn download(&self, folder: &str, name: &str) -> Task<Result<CameraFile>> {
+    self.fs().download(folder, name)
+  }
+
+  fn delete_file(&self, folder: &str, name: &str) -> Task<Result<()>> {
+    self.fs().delete_file(folder, name)
+  }
+
+  fn summary(&self) -> Result<String> {
+    Camera::summary(self)
+  }
+
+  fn config_snapshot(&self) -> Task<Result<ConfigSnapshot>> {
+    Camera::config_snapshot(self)
+  }
+
+  fn restore_snapshot(&self, snapshot: &ConfigSnapshot) -> Task<Result<Vec<ConfigKeyError>>> {
+    Camera::restore_snapshot(self, snapshot)
+  }
+}
+
+/// Call made to a [`MockCamera`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+  /// [`CameraOps::capture_image`]
+  CaptureImage,
+  /// [`CameraOps::trigger_capture`]
+  TriggerCapture,
+  /// [`CameraOps::capture_preview`]
+  CapturePreview,
+  /// [`CameraOps::wait_event`] with the timeout
+  WaitEvent(Duration),
+  /// [`CameraOps::download`]
+  Download {
+    /// Folder of the file
+    folder: String,
+    /// Name of the file
+    name: String,
+  },
+  /// [`CameraOps::delete_file`]
+  DeleteFile {
+    /// Folder of the file
+    folder: String,
+    /// Name of the file
+    name: String,
+  },
+  /// [`CameraOps::summary`]
+  Summary,
+  /// [`CameraOps::config_snapshot`]
+  ConfigSnapshot,
+  /// [`CameraOps::restore_snapshot`] with the restored snapshot
+  RestoreSnapshot(ConfigSnapshot),
+}
+
+struct MockFile {
+  folder: String,
+  name: String,
+  data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct MockState {
+  summary: String,
+  captures: VecDeque<Result<MockFile>>,
+  previews: VecDeque<Result<Vec<u8>>>,
+  events: VecDeque<Result<CameraEvent>>,
+  files: BTreeMap<(String, String), Vec<u8>>,
+  config: ConfigSnapshot,
+  calls: Vec<MockCall>,
+}
+
+impl MockState {
+  /// Take the next scripted capture and add the captured file
+  fn take_capture(&mut self) -> Result<CameraFilePath> {
+    let file = self
+      .captures
+      .pop_front()
+      .unwrap_or_else(|| Err(Error::not_supported("No capture scripted")))?;
+
+    let path = CameraFilePath::new(&file.folder, &file.name)?;
+    self.files.insert((file.folder, file.name), file.data);
+
+    Ok(path)
+  }
+}
+
+/// Camera returning scripted responses, for testing code using [`CameraOps`]
+///
+/// Responses are returned in the order they were pushed. Without a scripted response:
+///  - captures and previews fail with [`ErrorKind::NotSupported`](crate::error::ErrorKind::NotSupported)
+///  - [`wait_event`](CameraOps::wait_event) immediately returns [`CameraEvent::Timeout`]
+///  - downloads and deletions of files which don't exist fail with
+///    [`ErrorKind::FileNotFound`](crate::error::ErrorKind::FileNotFound)
+///
+/// [`trigger_capture`](CameraOps::trigger_capture) takes the next scripted capture and queues
+/// a [`CameraEvent::NewFile`] followed by [`CameraEvent::CaptureComplete`].
+///
+/// Cloning the mock is cheap, all clones share the same responses and recorded calls.
+#[derive(Clone)]
+pub struct MockCamera {
+  model: Arc<str>,
+  state: Arc<Mutex<MockState>>,
+}
+
+impl MockCamera {
+  /// Create a mock camera with the given model name
+  pub fn new(model: &str) -> Self {
+    Self { model: model.into(), state: Default::default() }
+  }
+
+  fn state(&self) -> MutexGuard<'_, MockState> {
+    self.state.lock().unwrap_or_else(|error| error.into_inner())
+  }
+
+  /// Script a successful capture creating the file `name` in `folder` with the given data
+  ///
+  /// The file is added to the files of the mock when it is captured, so it can be downloaded.
+  pub fn push_capture(&self, folder: &str, name: &str, data: Vec<u8>) {
+    let file = MockFile { folder: folder.to_owned(), name: name.to_owned(), data };
+    self.state().captures.push_back(Ok(file));
+  }
+
+  /// Script a failing capture
+  pub fn push_capture_error(&self, error: Error) {
+    self.state().captures.push_back(Err(error));
+  }
+
+  /// Script a preview returning `data`
+  pub fn push_preview(&self, data: Vec<u8>) {
+    self.state().previews.push_back(Ok(data));
+  }
+
+  /// Script a failing preview
+  pub fn push_preview_error(&self, error: Error) {
+    self.state().previews.push_back(Err(error));
+  }
+
+  /// Script an event returned by [`wait_event`](CameraOps::wait_event)
+  pub fn push_event(&self, event: CameraEvent) {
+    self.state().events.push_back(Ok(event));
+  }
+
+  /// Script a failing [`wait_event`](CameraOps::wait_event)
+  pub fn push_event_error(&self, error: Error) {
+    self.state().events.push_back(Err(error));
+  }
+
+  /// Add a file which can be downloaded
+  pub fn add_file(&self, folder: &str, name: &str, data: Vec<u8>) {
+    self.state().files.insert((folder.to_owned(), name.to_owned()), data);
+  }
+
+  /// Get the data of a file, `None` if it doesn't exist or was deleted
+  pub fn file(&self, folder: &str, name: &str) -> Option<Vec<u8>> {
+    self.state().files.get(&(folder.to_owned(), name.to_owned())).cloned()
+  }
+
+  /// Set the summary of the camera
+  pub fn set_summary(&self, summary: &str) {
+    self.state().summary = summary.to_owned();
+  }
+
+  /// Current configuration of the mock
+  pub fn config(&self) -> ConfigSnapshot {
+    self.state().config.clone()
+  }
+
+  /// Replace the configuration of the mock
+  pub fn set_config(&self, config: ConfigSnapshot) {
+    self.state().config = config;
+  }
+
+  /// Calls made to the mock so far, oldest first
+  pub fn calls(&self) -> Vec<MockCall> {
+    self.state().calls.clone()
+  }
+
+  /// Record a call and compute the response with the state of the mock
+  fn record<T>(&self, call: MockCall, f: impl FnOnce(&mut MockState) -> T) -> T {
+    let mut state = self.state();
+    state.calls.push(call);
+
+    f(&mut state)
+  }
+
+  /// Like [`MockCamera::record`], returning the response as [`Task`]
+  fn respond<T: 'static + Send>(
+    &self,
+    call: MockCall,
+    f: impl FnOnce(&mut MockState) -> T,
+  ) -> Task<T> {
+    let response = self.record(call, f);
+
+    unsafe { Task::new(move || response) }
+  }
+}
+
+fn file_not_found(folder: &str, name: &str) -> Error {
+  Error::new(libgphoto2_sys::GP_ERROR_FILE_NOT_FOUND, Some(format!("{folder}/{name}")))
+}
+
+impl CameraOps for MockCamera {
+  fn model(&self) -> String {
+    self.model.to_string()
+  }
+
+  fn capture_image(&self) -> Task<Result<CameraFilePath>> {
+    self.respond(MockCall::CaptureImage, |state| state.take_capture())
+  }
+
+  fn trigger_capture(&self) -> Task<Result<()>> {
+    self.respond(MockCall::TriggerCapture, |state| {
+      let path = state.take_capture()?;
+      state.events.push_back(Ok(CameraEvent::NewFile(path)));
+      state.events.push_back(Ok(CameraEvent::CaptureComplete));
+
+      Ok(())
+    })
+  }
+
+  fn capture_preview(&self) -> Task<Result<CameraFile>> {
+    let data = self.record(MockCall::CapturePreview, |state| {
+      state.previews.pop_front().unwrap_or_else(|| Err(Error::not_supported("No preview scripted")))
+    });
+
+    unsafe { Task::new(move || CameraFile::from_data(&data?)) }
+  }
+
+  fn wait_event(&self, timeout: Duration) -> Task<Result<CameraEvent>> {
+    self.respond(MockCall::WaitEvent(timeout), |state| {
+      state.events.pop_front().unwrap_or(Ok(CameraEvent::Timeout))
+    })
+  }
+
+  fn download(&self, folder: &str, name: &str) -> Task<Result<CameraFile>> {
+    let call = MockCall::Download { folder: folder.to_owned(), name: name.to_owned() };
+    let data = self.record(call, |state| {
+      state
+        .files
+        .get(&(folder.to_owned(), name.to_owned()))
+        .cloned()
+        .ok_or_else(|| file_not_found(folder, name))
+    });
+
+    unsafe { Task::new(move || CameraFile::from_data(&data?)) }
+  }
+
+  fn delete_file(&self, folder: &str, name: &str) -> Task<Result<()>> {
+    let call = MockCall::DeleteFile { folder: folder.to_owned(), name: name.to_owned() };
+    self.respond(call, |state| match state.files.remove(&(folder.to_owned(), name.to_owned())) {
+      Some(_) => Ok(()),
+      None => Err(file_not_found(folder, name)),
+    })
+  }
+
+  fn summary(&self) -> Result<String> {
+    self.record(MockCall::Summary, |state| Ok(state.summary.clone()))
+  }
+
+  fn config_snapshot(&self) -> Task<Result<ConfigSnapshot>> {
+    self.respond(MockCall::ConfigSnapshot, |state| Ok(state.config.clone()))
+  }
+
+  fn restore_snapshot(&self, snapshot: &ConfigSnapshot) -> Task<Result<Vec<ConfigKeyError>>> {
+    self.respond(MockCall::RestoreSnapshot(snapshot.clone()), |state| {
+      for (path, value) in snapshot.values() {
+        state.config.insert(path.clone(), value.clone());
+      }
+
+      Ok(Vec::new())
+    })
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{CameraOps, MockCall, MockCamera};
+  use crate::camera::CameraEvent;
+  use libgphoto2_sys::test_utils::SAMPLE_IMAGE;
+  use std::time::Duration;
+
+  fn capture(camera: &impl CameraOps) -> crate::Result<Vec<u8>> {
+    camera.trigger_capture().wait()?;
+
+    loop {
+      if let CameraEvent::NewFile(path) = camera.wait_event(Duration::from_secs(1)).wait()? {
+        let file = camera.download(&path.folder(), &path.name()).wait()?;
+        return Ok(file.data()?.to_vec());
+      }
+    }
+  }
+
+  #[test]
+  fn test_mock_camera() {
+    let camera = MockCamera::new("Mock DSLR");
+    camera.push_capture("/DCIM", "IMG_0001.JPG", SAMPLE_IMAGE.to_vec());
+
+    assert_eq!(capture(&camera).unwrap(), SAMPLE_IMAGE);
+    assert!(capture(&camera).unwrap_err().is_not_supported());
+    assert_eq!(
+      camera.calls()[..3],
+      [
+        MockCall::TriggerCapture,
+        MockCall::WaitEvent(Duration::from_secs(1)),
+        MockCall::Download { folder: "/DCIM".to_owned(), name: "IMG_0001.JPG".to_owned() },
+      ]
+    );
+  }
+}
@@ -0,0 +1,280 @@
+//! Camera hotplug monitoring
+//!
+//! A [`CameraMonitor`] watches the cameras available to libgphoto2 in a background thread
+//! and reports when cameras are connected or disconnected.
+//!
+//! With the `hotplug` feature, cameras are only detected again when libusb reports a USB
+//! device being plugged or unplugged. Without it, or when the platform's libusb does not
+//! support hotplug notifications, the monitor falls back to detecting cameras at a fixed interval.
+//!
+//! ## Reacting to new cameras
+//! ```no_run
+//! use gphoto2::monitor::{CameraMonitor, MonitorEvent};
+//! use gphoto2::{Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let monitor = CameraMonitor::new(&context, Duration::from_secs(1))?;
+//!
+//! for event in &monitor {
+//!   match event {
+//!     MonitorEvent::Connected(desc) => {
+//!       println!("{} connected on {}", desc.model, desc.port);
+//!       let camera = context.get_camera(&desc).wait()?;
+//!     }
+//!     MonitorEvent::Disconnected(desc) => println!("{} disconnected", desc.model),
+//!   }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{list::CameraDescriptor, Context, Result};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::{thread, time::Duration};
+
+/// Change in the set of available cameras
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MonitorEvent {
+  /// A camera was connected
+  Connected(CameraDescriptor),
+  /// A camera was disconnected
+  Disconnected(CameraDescriptor),
+}
+
+/// Watches for cameras being connected or disconnected
+///
+/// The monitor autodetects cameras in a background thread, cameras that are already
+/// connected when the monitor is created are reported as [`MonitorEvent::Connected`] first.
+///
+/// With the `hotplug` feature and a libusb supporting hotplug notifications, cameras are
+/// detected again whenever a USB device is plugged or unplugged
+/// (see [`CameraMonitor::uses_hotplug`]). Cameras on other ports (e.g. PTP/IP) are then only
+/// reported when the monitor starts or a USB device changes.
+///
+/// Otherwise the monitor falls back to autodetecting cameras every `interval`, which covers
+/// every port type supported by the loaded drivers.
+///
+/// The background thread stops when the monitor is dropped.
+pub struct CameraMonitor {
+  events: Receiver<MonitorEvent>,
+  hotplug: bool,
+  _stop: Sender<()>,
+}
+
+impl CameraMonitor {
+  /// Start monitoring cameras
+  ///
+  /// `interval` is the time between two detections when hotplug notifications are not available.
+  pub fn new(context: &Context, interval: Duration) -> Result<Self> {
+    let context = context.clone();
+    let (event_sender, events) = crossbeam_channel::unbounded();
+    let (stop, stop_receiver) = crossbeam_channel::bounded(0);
+
+    let wakeup = Wakeup::new(interval);
+    let hotplug = wakeup.is_hotplug();
+
+    thread::Builder::new()
+      .name("gphoto2-monitor".to_owned())
+      .spawn(move || monitor_thread(context, wakeup, event_sender, stop_receiver))?;
+
+    Ok(Self { events, hotplug, _stop: stop })
+  }
+
+  /// Whether cameras are detected on USB hotplug notifications instead of at a fixed interval
+  ///
+  /// Always `false` without the `hotplug` feature.
+  pub fn uses_hotplug(&self) -> bool {
+    self.hotplug
+  }
+
+  /// Receiver of the monitor events
+  ///
+  /// Can be used with [`crossbeam_channel::select!`] to wait on other channels at the same time.
+  pub fn receiver(&self) -> &Receiver<MonitorEvent> {
+    &self.events
+  }
+
+  /// Wait for the next event
+  pub fn recv(&self) -> Option<MonitorEvent> {
+    self.events.recv().ok()
+  }
+
+  /// Wait for the next event with a timeout
+  pub fn recv_timeout(&self, timeout: Duration) -> Option<MonitorEvent> {
+    self.events.recv_timeout(timeout).ok()
+  }
+
+  /// Get the next event if one is available without waiting
+  pub fn try_recv(&self) -> Option<MonitorEvent> {
+    self.events.try_recv().ok()
+  }
+}
+
+impl<'a> IntoIterator for &'a CameraMonitor {
+  type Item = MonitorEvent;
+  type IntoIter = crossbeam_channel::Iter<'a, MonitorEvent>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.events.iter()
+  }
+}
+
+/// Decides when the monitor thread detects cameras again
+enum Wakeup {
+  Interval(Duration),
+  #[cfg(feature = "hotplug")]
+  Hotplug(hotplug::UsbWatcher),
+}
+
+impl Wakeup {
+  fn new(interval: Duration) -> Self {
+    #[cfg(feature = "hotplug")]
+    {
+      if let Some(watcher) = hotplug::UsbWatcher::new() {
+        return Self::Hotplug(watcher);
+      }
+    }
+
+    Self::Interval(interval)
+  }
+
+  fn is_hotplug(&self) -> bool {
+    match self {
+      Self::Interval(_) => false,
+      #[cfg(feature = "hotplug")]
+      Self::Hotplug(_) => true,
+    }
+  }
+
+  /// Waits until cameras should be detected again, returns `false` if the monitor was dropped
+  fn wait(&self, stop: &Receiver<()>) -> bool {
+    match self {
+      // The monitor dropping its sender disconnects the channel and stops the thread
+      Self::Interval(interval) => {
+        matches!(stop.recv_timeout(*interval), Err(RecvTimeoutError::Timeout))
+      }
+      #[cfg(feature = "hotplug")]
+      Self::Hotplug(watcher) => watcher.wait(stop),
+    }
+  }
+}
+
+#[cfg(feature = "hotplug")]
+mod hotplug {
+  use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError};
+  use rusb::UsbContext;
+  use std::time::Duration;
+
+  /// Longest time spent in libusb before checking if the monitor was dropped
+  const EVENT_TIMEOUT: Duration = Duration::from_millis(200);
+  /// Time given to a new device to settle (e.g. udev changing its permissions) before detecting
+  const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+  struct Notifier(Sender<()>);
+
+  impl<T: UsbContext> rusb::Hotplug<T> for Notifier {
+    fn device_arrived(&mut self, _device: rusb::Device<T>) {
+      self.0.send(()).ok();
+    }
+
+    fn device_left(&mut self, _device: rusb::Device<T>) {
+      self.0.send(()).ok();
+    }
+  }
+
+  /// Receives libusb hotplug notifications for all USB devices
+  pub(super) struct UsbWatcher {
+    context: rusb::Context,
+    changes: Receiver<()>,
+    _registration: rusb::Registration<rusb::Context>,
+  }
+
+  impl UsbWatcher {
+    /// Registers for hotplug notifications, `None` if they are not supported
+    pub(super) fn new() -> Option<Self> {
+      if !rusb::has_hotplug() {
+        log::info!("libusb does not support hotplug, polling for cameras instead");
+        return None;
+      }
+
+      let (sender, changes) = crossbeam_channel::unbounded();
+
+      let registration = rusb::Context::new().and_then(|context| {
+        let registration =
+          rusb::HotplugBuilder::new().register(&context, Box::new(Notifier(sender)))?;
+        Ok((context, registration))
+      });
+
+      match registration {
+        Ok((context, registration)) => Some(Self { context, changes, _registration: registration }),
+        Err(error) => {
+          log::warn!("Could not register for USB hotplug, polling for cameras instead: {}", error);
+          None
+        }
+      }
+    }
+
+    /// Waits for a USB device to be plugged or unplugged, returns `false` if the monitor was dropped
+    pub(super) fn wait(&self, stop: &Receiver<()>) -> bool {
+      loop {
+        if let Err(error) = self.context.handle_events(Some(EVENT_TIMEOUT)) {
+          log::warn!("Could not handle USB events: {}", error);
+        }
+
+        if let Err(TryRecvError::Disconnected) = stop.try_recv() {
+          return false;
+        }
+
+        if self.changes.try_iter().count() > 0 {
+          if !matches!(stop.recv_timeout(SETTLE_TIME), Err(RecvTimeoutError::Timeout)) {
+            return false;
+          }
+
+          // Devices changed while settling are covered by the next detection
+          self.context.handle_events(Some(Duration::ZERO)).ok();
+          self.changes.try_iter().for_each(drop);
+
+          return true;
+        }
+      }
+    }
+  }
+}
+
+fn monitor_thread(
+  context: Context,
+  wakeup: Wakeup,
+  events: Sender<MonitorEvent>,
+  stop: Receiver<()>,
+) {
+  let mut known: Vec<CameraDescriptor> = Vec::new();
+
+  loop {
+    match context.list_cameras().wait() {
+      Ok(cameras) => {
+        let current: Vec<CameraDescriptor> = cameras.collect();
+
+        let disconnected = known.iter().filter(|desc| !current.contains(desc)).cloned();
+        let connected = current.iter().filter(|desc| !known.contains(desc)).cloned();
+
+        for event in
+          disconnected.map(MonitorEvent::Disconnected).chain(connected.map(MonitorEvent::Connected))
+        {
+          if events.send(event).is_err() {
+            return;
+          }
+        }
+
+        known = current;
+      }
+      Err(error) => log::warn!("Could not detect cameras: {}", error),
+    }
+
+    if !wakeup.wait(&stop) {
+      return;
+    }
+  }
+}
@@ -0,0 +1,376 @@
+//! Managing multiple cameras
+//!
+//! A [`CameraPool`] holds several cameras and merges their events into a single stream,
+//! which is the base for multi-camera setups like photogrammetry rigs. An [`EventMux`] only
+//! merges the events of cameras, which can be added and removed while it is running.
+//!
+//! ## Opening all connected cameras
+//! ```no_run
+//! use gphoto2::{camera::CameraEvent, pool::CameraPool, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let pool = CameraPool::open_all(&context, Duration::from_millis(50))?;
+//!
+//! for camera in pool.cameras() {
+//!   camera.trigger_capture().wait()?;
+//! }
+//!
+//! for pool_event in pool.events() {
+//!   if let Ok(CameraEvent::NewFile(file)) = pool_event.event {
+//!     println!("Camera {} captured {}", pool_event.index, file.name());
+//!   }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  camera::CameraEvent,
+  file::CameraFilePath,
+  list::CameraDescriptor,
+  task::Task,
+  worker::{event_worker, PauseGate},
+  Camera, Context, Error, Result,
+};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+  collections::HashMap,
+  fmt,
+  sync::Arc,
+  thread,
+  time::{Duration, Instant},
+};
+
+/// Event of one of the cameras in a [`CameraPool`]
+#[derive(Debug)]
+pub struct PoolEvent {
+  /// Index of the camera in the pool
+  pub index: usize,
+  /// The event, or the error that occurred while waiting for it
+  pub event: Result<CameraEvent>,
+}
+
+/// Set of cameras with a merged event stream
+///
+/// Every camera gets a background worker thread which waits for its events and forwards them
+/// to the merged stream returned by [`CameraPool::events`], [`CameraEvent::Timeout`] events are
+/// not forwarded.
+///
+/// Commands are sent to the cameras by calling the usual methods on [`CameraPool::get`] or
+/// [`CameraPool::cameras`], their [`Task`]s act as the response channels.
+///
+/// All libgphoto2 calls run on a single background thread, each event worker occupies it for
+/// up to `poll_timeout` at a time. Short poll timeouts keep the cameras responsive to commands.
+///
+/// The worker threads stop when the pool is dropped.
+pub struct CameraPool {
+  cameras: Vec<Camera>,
+  events: Receiver<PoolEvent>,
+  pause: Arc<PauseGate>,
+  _stop: Sender<()>,
+}
+
+impl CameraPool {
+  /// Create a pool from already opened cameras
+  ///
+  /// The index of each camera in `cameras` is used as its index in the pool.
+  pub fn new(cameras: Vec<Camera>, poll_timeout: Duration) -> Result<Self> {
+    let (event_sender, events) = crossbeam_channel::unbounded();
+    let (stop, stop_receiver) = crossbeam_channel::bounded(0);
+    let pause = Arc::new(PauseGate::default());
+
+    for (index, camera) in cameras.iter().enumerate() {
+      let camera = camera.clone();
+      let events = event_sender.clone();
+      let stop = stop_receiver.clone();
+      let pause = pause.clone();
+
+      thread::Builder::new().name(format!("gphoto2-pool-{index}")).spawn(move || {
+        event_worker(camera, poll_timeout, stop, Some(&pause), |event| {
+          events.send(PoolEvent { index, event }).is_ok()
+        })
+      })?;
+    }
+
+    Ok(Self { cameras, events, pause, _stop: stop })
+  }
+
+  /// Open every camera detected by the context
+  pub fn open_all(context: &Context, poll_timeout: Duration) -> Result<Self> {
+    let descriptors: Vec<CameraDescriptor> = context.list_cameras().wait()?.collect();
+
+    Self::open(context, &descriptors, poll_timeout)
+  }
+
+  /// Open the cameras described by `descriptors`
+  pub fn open(
+    context: &Context,
+    descriptors: &[CameraDescriptor],
+    poll_timeout: Duration,
+  ) -> Result<Self> {
+    let mut tasks: Vec<Task<Result<Camera>>> =
+      descriptors.iter().map(|descriptor| context.get_camera(descriptor)).collect();
+
+    // Queue all initializations at once, the results are collected in order
+    tasks.iter_mut().for_each(Task::background);
+    let cameras = tasks.into_iter().map(Task::wait).collect::<Result<Vec<_>>>()?;
+
+    Self::new(cameras, poll_timeout)
+  }
+
+  /// Number of cameras in the pool
+  pub fn len(&self) -> usize {
+    self.cameras.len()
+  }
+
+  /// Check if the pool has no cameras
+  pub fn is_empty(&self) -> bool {
+    self.cameras.is_empty()
+  }
+
+  /// Get the camera at an index
+  pub fn get(&self, index: usize) -> Option<&Camera> {
+    self.cameras.get(index)
+  }
+
+  /// All cameras in the pool
+  pub fn cameras(&self) -> &[Camera] {
+    &self.cameras
+  }
+
+  /// Merged events of all cameras
+  pub fn events(&self) -> &Receiver<PoolEvent> {
+    &self.events
+  }
+
+  /// Trigger all cameras as simultaneously as possible and collect the captured files
+  ///
+  /// Before triggering, the event workers are paused, which waits for their pending polls
+  /// (up to the poll timeout of the pool), so the triggers are not queued behind them.
+  /// Events that are still pending on the cameras or in the merged stream are discarded at this
+  /// point, so files captured earlier are not attributed to this trigger.
+  ///
+  /// The trigger commands for all cameras are then dispatched back to back. As all libgphoto2
+  /// calls run on a single thread, each camera is triggered after the trigger command of the
+  /// previous one completed: the skew between the first and the last camera is the sum of the
+  /// trigger latencies of the cameras before it, typically a few milliseconds to tens of
+  /// milliseconds per camera depending on the model and port. Rigs which need tighter
+  /// synchronization should use a hardware trigger.
+  ///
+  /// Afterwards the workers are resumed and the merged event stream is read until every camera
+  /// that was triggered successfully reported a [`CameraEvent::NewFile`] or `timeout` elapsed.
+  ///
+  /// Returns one result per camera, in pool order. Other events received while waiting
+  /// for the files are discarded.
+  ///
+  /// ```no_run
+  /// use gphoto2::{pool::CameraPool, Context, Result};
+  /// use std::time::Duration;
+  ///
+  /// # fn main() -> Result<()> {
+  /// let context = Context::new()?;
+  /// let pool = CameraPool::open_all(&context, Duration::from_millis(20))?;
+  ///
+  /// for (index, file) in pool.trigger_all(Duration::from_secs(10)).into_iter().enumerate() {
+  ///   println!("Camera {}: {:?}", index, file);
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn trigger_all(&self, timeout: Duration) -> Vec<Result<CameraFilePath>> {
+    let paused = self.pause.pause();
+
+    for camera in &self.cameras {
+      discard_pending_events(camera);
+    }
+    self.events.try_iter().for_each(drop);
+
+    let mut tasks: Vec<Task<Result<()>>> =
+      self.cameras.iter().map(|camera| camera.trigger_capture()).collect();
+
+    tasks.iter_mut().for_each(Task::background);
+
+    let mut results: Vec<Option<Result<CameraFilePath>>> =
+      tasks.into_iter().map(|task| task.wait().err().map(Err)).collect();
+
+    drop(paused);
+
+    let deadline = Instant::now() + timeout;
+
+    while results.iter().any(Option::is_none) {
+      let Ok(PoolEvent { index, event }) = self.events.recv_deadline(deadline) else { break };
+
+      if results[index].is_some() {
+        continue;
+      }
+
+      match event {
+        Ok(CameraEvent::NewFile(file)) => results[index] = Some(Ok(file)),
+        Err(error) => results[index] = Some(Err(error)),
+        Ok(_) => {}
+      }
+    }
+
+    results
+      .into_iter()
+      .map(|result| {
+        result.unwrap_or_else(|| Err(Error::new(libgphoto2_sys::GP_ERROR_TIMEOUT, None)))
+      })
+      .collect()
+  }
+}
+
+/// Identifier of a camera in an [`EventMux`]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CameraId(u64);
+
+impl fmt::Display for CameraId {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "#{}", self.0)
+  }
+}
+
+/// Event of one of the cameras in an [`EventMux`]
+#[derive(Debug)]
+pub struct MuxEvent {
+  /// Camera the event belongs to
+  pub camera: CameraId,
+  /// The event, or the error that occurred while waiting for it
+  pub event: Result<CameraEvent>,
+}
+
+/// Merges the events of cameras which can be added and removed at any time
+///
+/// Like in a [`CameraPool`], every camera gets a worker thread waiting for its events, which are
+/// forwarded to the stream returned by [`EventMux::events`]. [`CameraEvent::Timeout`] events are
+/// not forwarded.
+///
+/// The workers stop when their camera is removed or the mux is dropped.
+///
+/// ```no_run
+/// use gphoto2::{camera::CameraEvent, pool::EventMux, Context, Result};
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<()> {
+/// let context = Context::new()?;
+/// let mut mux = EventMux::new(Duration::from_millis(50));
+///
+/// let mut cameras = Vec::new();
+/// for descriptor in context.list_cameras().wait()? {
+///   let camera = context.get_camera(&descriptor).wait()?;
+///   cameras.push((mux.add(&camera)?, camera));
+/// }
+///
+/// for mux_event in mux.events() {
+///   if let Ok(CameraEvent::NewFile(file)) = mux_event.event {
+///     println!("Camera {} captured {}", mux_event.camera, file);
+///   }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventMux {
+  poll_timeout: Duration,
+  sender: Sender<MuxEvent>,
+  events: Receiver<MuxEvent>,
+  workers: HashMap<CameraId, Sender<()>>,
+  next_id: u64,
+}
+
+impl EventMux {
+  /// Create an empty mux, each worker waits up to `poll_timeout` for an event at a time
+  ///
+  /// See [`CameraPool`] for the effect of the poll timeout.
+  pub fn new(poll_timeout: Duration) -> Self {
+    let (sender, events) = crossbeam_channel::unbounded();
+
+    Self { poll_timeout, sender, events, workers: HashMap::new(), next_id: 0 }
+  }
+
+  /// Start forwarding the events of a camera
+  pub fn add(&mut self, camera: &Camera) -> Result<CameraId> {
+    let id = CameraId(self.next_id);
+    let (stop, stop_receiver) = crossbeam_channel::bounded(0);
+    let (camera, events, poll_timeout) = (camera.clone(), self.sender.clone(), self.poll_timeout);
+
+    thread::Builder::new().name(format!("gphoto2-mux-{}", id.0)).spawn(move || {
+      event_worker(camera, poll_timeout, stop_receiver, None, |event| {
+        events.send(MuxEvent { camera: id, event }).is_ok()
+      })
+    })?;
+
+    self.next_id += 1;
+    self.workers.insert(id, stop);
+
+    Ok(id)
+  }
+
+  /// Stop forwarding the events of a camera, returns false if the camera was not in the mux
+  ///
+  /// Events which were already forwarded stay in the stream.
+  pub fn remove(&mut self, id: CameraId) -> bool {
+    self.workers.remove(&id).is_some()
+  }
+
+  /// Number of cameras in the mux
+  pub fn len(&self) -> usize {
+    self.workers.len()
+  }
+
+  /// Check if the mux has no cameras
+  pub fn is_empty(&self) -> bool {
+    self.workers.is_empty()
+  }
+
+  /// Merged events of all cameras
+  ///
+  /// The stream stays open while the mux exists, even if it has no cameras.
+  pub fn events(&self) -> &Receiver<MuxEvent> {
+    &self.events
+  }
+}
+
+/// Drop the events already queued on a camera
+fn discard_pending_events(camera: &Camera) {
+  loop {
+    match camera.wait_event(Duration::ZERO).wait() {
+      Ok(CameraEvent::Timeout) | Err(_) => break,
+      Ok(event) => log::debug!("Discarding event before trigger: {:?}", event),
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::EventMux;
+  use crate::sample_camera;
+  use std::time::Duration;
+
+  #[test]
+  fn test_event_mux() {
+    let camera = sample_camera();
+    let mut mux = EventMux::new(Duration::from_millis(20));
+    assert!(mux.is_empty());
+
+    let first = mux.add(&camera).unwrap();
+    let second = mux.add(&camera).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(second.to_string(), "#1");
+    assert_eq!(mux.len(), 2);
+
+    assert!(mux.remove(first));
+    assert!(!mux.remove(first));
+    assert_eq!(mux.len(), 1);
+
+    assert!(mux.remove(second));
+    assert!(mux.is_empty());
+
+    // The stream stays open without cameras
+    assert!(!matches!(
+      mux.events().recv_timeout(Duration::from_millis(50)),
+      Err(crossbeam_channel::RecvTimeoutError::Disconnected)
+    ));
+  }
+}
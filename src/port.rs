@@ -17,10 +17,14 @@
 //! ```
 
 use crate::{
-  helper::{as_ref, chars_to_string},
-  try_gp_internal, Result,
+  helper::{as_ref, chars_to_string, to_c_string, with_libgphoto2_env},
+  task::BackgroundPtr,
+  try_gp_internal, Context, Result,
 };
-use std::{fmt, marker::PhantomData};
+use std::{ffi, fmt, marker::PhantomData, ops::Range, os::raw::c_int};
+
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
 
 /// Type of the port
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -66,13 +70,56 @@ impl PortInfo<'_> {
   }
 }
 
-pub(crate) struct PortInfoList {
-  pub(crate) inner: *mut libgphoto2_sys::GPPortInfoList,
+/// List of all ports available to libgphoto2
+///
+/// Contains one entry for every port found by the loaded I/O drivers
+/// (usb, ptpip, serial, disk, ...) and can be loaded with [`Context::list_ports`](crate::Context::list_ports).
+///
+/// ## Example
+///
+/// ```no_run
+/// use gphoto2::{Context, Result};
+///
+/// # fn main() -> Result<()> {
+/// let context = Context::new()?;
+///
+/// for port in context.list_ports().wait()?.iter() {
+///   println!("{} at {} ({:?})", port.name(), port.path(), port.port_type());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PortInfoList {
+  pub(crate) inner: BackgroundPtr<libgphoto2_sys::GPPortInfoList>,
+}
+
+/// Iterator over the [`PortInfo`]s of a [`PortInfoList`]
+pub struct PortInfoIter<'a> {
+  list: &'a PortInfoList,
+  range: Range<c_int>,
+}
+
+impl<'a> Iterator for PortInfoIter<'a> {
+  type Item = PortInfo<'a>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.range.next().map(|i| self.list.get_unchecked(i))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.range.size_hint()
+  }
+}
+
+impl ExactSizeIterator for PortInfoIter<'_> {
+  fn len(&self) -> usize {
+    self.range.len()
+  }
 }
 
 impl Drop for PortInfoList {
   fn drop(&mut self) {
-    try_gp_internal!(gp_port_info_list_free(self.inner).unwrap());
+    try_gp_internal!(gp_port_info_list_free(*self.inner).unwrap());
   }
 }
 
@@ -86,7 +133,18 @@ impl fmt::Debug for PortInfo<'_> {
   }
 }
 
-as_ref!(PortInfoList -> libgphoto2_sys::GPPortInfoList, *self.inner);
+#[cfg(feature = "serde")]
+impl serde::Serialize for PortInfo<'_> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("PortInfo", 3)?;
+    s.serialize_field("name", &self.name())?;
+    s.serialize_field("path", &self.path())?;
+    s.serialize_field("port_type", &self.port_type())?;
+    s.end()
+  }
+}
+
+as_ref!(PortInfoList -> libgphoto2_sys::GPPortInfoList, **self.inner);
 
 as_ref!(PortInfo<'_> -> libgphoto2_sys::GPPortInfo, self.inner);
 
@@ -132,16 +190,84 @@ impl PortInfo<'_> {
 
 impl PortInfoList {
   /// Must be called from a [`Task`]
-  pub(crate) fn new_inner() -> Result<Self> {
+  ///
+  /// Loads the drivers from the directory set with
+  /// [`ContextBuilder::iolibs_dir`](crate::context::ContextBuilder::iolibs_dir), if any.
+  pub(crate) fn new_inner(context: &Context) -> Result<Self> {
     try_gp_internal!(gp_port_info_list_new(&out port_info_list)?);
-    try_gp_internal!(gp_port_info_list_load(port_info_list)?);
+    let list = Self { inner: BackgroundPtr(port_info_list) };
+
+    let load = || -> Result<()> {
+      try_gp_internal!(gp_port_info_list_load(port_info_list)?);
+      Ok(())
+    };
 
-    Ok(Self { inner: port_info_list })
+    match context.iolibs_dir() {
+      // libgphoto2 has no API to load port drivers from a directory, IOLIBS is only read here.
+      // The loaded ports remember the path of their driver, so it's only set while loading.
+      Some(dir) => with_libgphoto2_env("IOLIBS", dir, load)?,
+      None => load()?,
+    }
+
+    Ok(list)
   }
 
   pub(crate) fn get_port_info(&self, p: i32) -> Result<PortInfo<'_>> {
-    try_gp_internal!(gp_port_info_list_get_info(self.inner, p, &out port_info)?);
+    try_gp_internal!(gp_port_info_list_get_info(*self.inner, p, &out port_info)?);
 
     Ok(unsafe { PortInfo::new(port_info) })
   }
+
+  fn count(&self) -> c_int {
+    try_gp_internal!(let count = gp_port_info_list_count(*self.inner).unwrap());
+    count
+  }
+
+  fn get_unchecked(&self, index: c_int) -> PortInfo<'_> {
+    self.get_port_info(index).unwrap()
+  }
+
+  /// Number of ports in the list
+  pub fn len(&self) -> usize {
+    self.count().try_into().unwrap()
+  }
+
+  /// Check if the list is empty
+  pub fn is_empty(&self) -> bool {
+    self.count() == 0
+  }
+
+  /// Get the port at an index
+  pub fn get(&self, index: usize) -> Option<PortInfo<'_>> {
+    let index = c_int::try_from(index).ok().filter(|index| *index < self.count())?;
+
+    Some(self.get_unchecked(index))
+  }
+
+  /// Find a port by its path, e.g. `usb:001,004`
+  pub fn find_path(&self, path: &str) -> Result<PortInfo<'_>> {
+    try_gp_internal!(let index = gp_port_info_list_lookup_path(*self.inner, to_c_string!(path))?);
+
+    self.get_port_info(index)
+  }
+
+  /// Iterate over all ports
+  pub fn iter(&self) -> PortInfoIter<'_> {
+    PortInfoIter { list: self, range: 0..self.count() }
+  }
+}
+
+impl<'a> IntoIterator for &'a PortInfoList {
+  type Item = PortInfo<'a>;
+  type IntoIter = PortInfoIter<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl fmt::Debug for PortInfoList {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
 }
@@ -0,0 +1,185 @@
+//! Named configuration presets
+//!
+//! A [`PresetStore`] keeps [`ConfigSnapshot`]s under a name (e.g. `studio-strobe`) as JSON files
+//! in a directory. Presets can be full snapshots of a camera or only contain a few settings,
+//! see [`ConfigSnapshot::insert`].
+//!
+//! Requires the `presets` feature.
+//!
+//! ## Saving and applying presets
+//! ```no_run
+//! use gphoto2::{presets::PresetStore, Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//! let presets = PresetStore::open("presets")?;
+//!
+//! presets.save("studio-strobe", &camera.config_snapshot().wait()?)?;
+//!
+//! if let Some((name, differences)) = presets.closest(&camera)? {
+//!   println!("Closest preset is {} with {} differences", name, differences);
+//! }
+//!
+//! for error in presets.apply("timelapse-night", &camera)? {
+//!   eprintln!("{}", error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  config::{ConfigKeyError, ConfigSnapshot},
+  Camera, Error, Result,
+};
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+};
+
+const EXTENSION: &str = "json";
+
+/// Directory of named [`ConfigSnapshot`]s
+#[derive(Debug, Clone)]
+pub struct PresetStore {
+  dir: PathBuf,
+}
+
+impl PresetStore {
+  /// Open a preset directory, it is created if it doesn't exist
+  pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+    let dir = dir.into();
+    fs::create_dir_all(&dir)?;
+
+    Ok(Self { dir })
+  }
+
+  /// Directory of the presets
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Names of all stored presets, sorted
+  pub fn names(&self) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+
+    for entry in fs::read_dir(&self.dir)? {
+      let path = entry?.path();
+
+      if path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+        continue;
+      }
+
+      if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+        names.push(name.to_owned());
+      }
+    }
+
+    names.sort();
+    Ok(names)
+  }
+
+  /// Store a snapshot as preset, replacing an existing preset with the same name
+  pub fn save(&self, name: &str, snapshot: &ConfigSnapshot) -> Result<()> {
+    let json = serde_json::to_vec_pretty(snapshot).map_err(io::Error::from)?;
+    fs::write(self.path(name)?, json)?;
+
+    Ok(())
+  }
+
+  /// Load a preset
+  pub fn load(&self, name: &str) -> Result<ConfigSnapshot> {
+    let json = fs::read(self.path(name)?)?;
+
+    Ok(serde_json::from_slice(&json).map_err(io::Error::from)?)
+  }
+
+  /// Delete a preset
+  pub fn remove(&self, name: &str) -> Result<()> {
+    fs::remove_file(self.path(name)?)?;
+
+    Ok(())
+  }
+
+  /// Apply a preset to a camera, see [`Camera::restore_snapshot`]
+  pub fn apply(&self, name: &str, camera: &Camera) -> Result<Vec<ConfigKeyError>> {
+    let snapshot = self.load(name)?;
+
+    camera.restore_snapshot(&snapshot).wait()
+  }
+
+  /// Find the preset closest to the current configuration of the camera
+  ///
+  /// Returns the name of the preset and the number of its settings which differ from the
+  /// camera, or `None` if there are no presets. A preset matches exactly if the number is 0.
+  pub fn closest(&self, camera: &Camera) -> Result<Option<(String, usize)>> {
+    let current = camera.config_snapshot().wait()?;
+    let mut closest: Option<(String, usize)> = None;
+
+    for name in self.names()? {
+      let differences = differences(&self.load(&name)?, &current);
+
+      match &closest {
+        Some((_, closest_differences)) if *closest_differences <= differences => {}
+        _ => closest = Some((name, differences)),
+      }
+    }
+
+    Ok(closest)
+  }
+
+  fn path(&self, name: &str) -> Result<PathBuf> {
+    let is_valid = !name.is_empty()
+      && !name.starts_with('.')
+      && !name.contains(|c: char| matches!(c, '/' | '\\') || c.is_control());
+
+    if !is_valid {
+      return Err(Error::bad_parameters(format!("Invalid preset name {name:?}")));
+    }
+
+    Ok(self.dir.join(format!("{name}.{EXTENSION}")))
+  }
+}
+
+/// Number of settings in `preset` which are missing or different in `current`
+fn differences(preset: &ConfigSnapshot, current: &ConfigSnapshot) -> usize {
+  preset.values().iter().filter(|(path, value)| current.get(path) != Some(*value)).count()
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::PresetStore;
+  use crate::{config::ConfigSnapshot, widget::WidgetValue};
+
+  #[test]
+  fn test_path() {
+    let store = PresetStore { dir: "presets".into() };
+
+    assert_eq!(store.path("studio-strobe").unwrap(), store.dir().join("studio-strobe.json"));
+    assert_eq!(store.path("night sky").unwrap(), store.dir().join("night sky.json"));
+
+    for name in ["", ".hidden", "..", "../escape", "a/b", "a\\b", "tab\there"] {
+      assert!(store.path(name).is_err(), "{name:?} should be invalid");
+    }
+  }
+
+  #[test]
+  fn test_save_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = PresetStore::open(dir.path().join("presets")).unwrap();
+
+    let mut snapshot = ConfigSnapshot::default();
+    snapshot.insert("/main/imgsettings/iso", WidgetValue::Choice("400".into()));
+    snapshot.insert("/main/capturesettings/exposurecompensation", WidgetValue::Range(-0.3));
+
+    store.save("b", &snapshot).unwrap();
+    store.save("a", &ConfigSnapshot::default()).unwrap();
+    std::fs::write(store.dir().join("notes.txt"), "not a preset").unwrap();
+
+    assert_eq!(store.names().unwrap(), ["a", "b"]);
+    assert_eq!(store.load("b").unwrap(), snapshot);
+
+    store.remove("a").unwrap();
+    assert_eq!(store.names().unwrap(), ["b"]);
+    assert!(store.load("a").is_err());
+  }
+}
@@ -0,0 +1,138 @@
+//! Live view preview streaming
+//!
+//! ## Streaming preview frames
+//! ```no_run
+//! use gphoto2::{Context, Result};
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let camera = context.autodetect_camera().wait()?;
+//!
+//! let mut stream = camera.preview_stream()?;
+//!
+//! for _ in 0..100 {
+//!   let frame = stream.next_frame().wait()?;
+//!   println!("Got {} frame of {} bytes", frame.mime_type(), frame.data().len());
+//!
+//!   // Hand the buffer back so the next frame doesn't allocate
+//!   stream.recycle(frame);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  file::CameraFile, helper::chars_to_string, task::Task, try_gp_internal, widget::ToggleWidget,
+  Camera, Result,
+};
+use std::fmt;
+
+/// Single frame of a [`PreviewStream`]
+pub struct PreviewFrame {
+  data: Vec<u8>,
+  mime_type: String,
+}
+
+impl PreviewFrame {
+  /// Image data of the frame
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// Mime type of the frame, usually `image/jpeg`
+  pub fn mime_type(&self) -> &str {
+    &self.mime_type
+  }
+
+  /// Take ownership of the frame data
+  pub fn into_data(self) -> Vec<u8> {
+    self.data
+  }
+}
+
+impl fmt::Debug for PreviewFrame {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PreviewFrame")
+      .field("mime_type", &self.mime_type)
+      .field("size", &self.data.len())
+      .finish()
+  }
+}
+
+/// Continuous stream of live view frames
+///
+/// Created by [`Camera::preview_stream`]. If the camera has a `viewfinder` widget,
+/// it is enabled when the stream is created and disabled again when the stream is dropped.
+///
+/// The stream also implements [`Iterator`], which blocks on every frame and never ends,
+/// use [`Iterator::take`] to limit the number of frames.
+pub struct PreviewStream {
+  camera: Camera,
+  file: CameraFile,
+  viewfinder: Option<ToggleWidget>,
+  buffer: Option<Vec<u8>>,
+}
+
+impl PreviewStream {
+  pub(crate) fn new(camera: Camera) -> Result<Self> {
+    let viewfinder = camera.config_key::<ToggleWidget>("viewfinder").wait().ok();
+
+    if let Some(viewfinder) = &viewfinder {
+      viewfinder.try_set_toggled(true)?;
+      camera.set_config(viewfinder).wait()?;
+    }
+
+    Ok(Self { camera, file: CameraFile::new()?, viewfinder, buffer: None })
+  }
+
+  /// Capture the next frame
+  ///
+  /// The frame data is written into a recycled buffer if one was
+  /// handed back with [`PreviewStream::recycle`].
+  pub fn next_frame(&mut self) -> Task<Result<PreviewFrame>> {
+    let camera = self.camera.camera;
+    let context = self.camera.context.inner;
+    let file = self.file.clone();
+    let mut buffer = self.buffer.take().unwrap_or_default();
+
+    unsafe {
+      Task::new(move || {
+        try_gp_internal!(gp_camera_capture_preview(*camera, *file.inner, *context)?);
+        try_gp_internal!(gp_file_get_data_and_size(*file.inner, &out data, &out size)?);
+        try_gp_internal!(gp_file_get_mime_type(*file.inner, &out mime_type)?);
+
+        buffer.clear();
+        buffer.extend_from_slice(std::slice::from_raw_parts(data.cast::<u8>(), size.try_into()?));
+
+        Ok(PreviewFrame { data: buffer, mime_type: chars_to_string(mime_type) })
+      })
+    }
+    .context(context)
+    .for_camera(&self.camera)
+  }
+
+  /// Hand the buffer of a frame back to the stream so it can be reused for the next frame
+  pub fn recycle(&mut self, frame: PreviewFrame) {
+    self.buffer = Some(frame.data);
+  }
+}
+
+impl Iterator for PreviewStream {
+  type Item = Result<PreviewFrame>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    Some(self.next_frame().wait())
+  }
+}
+
+impl Drop for PreviewStream {
+  fn drop(&mut self) {
+    if let Some(viewfinder) = &self.viewfinder {
+      viewfinder.set_toggled(false);
+
+      if let Err(error) = self.camera.set_config(viewfinder).wait() {
+        log::warn!("Could not disable viewfinder: {}", error);
+      }
+    }
+  }
+}
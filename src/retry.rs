@@ -0,0 +1,203 @@
+//! Retrying operations on busy cameras
+//!
+//! PTP cameras routinely reject commands while they are busy, e.g. while writing
+//! an image to the card. A [`RetryPolicy`] retries such operations with exponential backoff.
+//!
+//! ## Retrying a capture
+//! ```no_run
+//! use gphoto2::{retry::RetryPolicy, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let mut camera = Context::new()?.autodetect_camera().wait()?;
+//! camera.set_retry_policy(RetryPolicy::default().max_attempts(10));
+//!
+//! let file = camera.with_retry(|camera| camera.capture_image())?;
+//!
+//! // Policies can also be used for single calls
+//! let policy = RetryPolicy::default().initial_delay(Duration::from_millis(500));
+//! let storages = policy.run(|| camera.storages().wait())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{error::ErrorKind, Error, Result};
+use std::{thread, time::Duration};
+
+/// Policy for retrying operations which failed with a transient error
+///
+/// By default, operations failing because the camera is busy are attempted up to 5 times,
+/// waiting 100ms before the first retry and doubling the delay up to 2s for every further retry.
+/// I/O errors are only retried if enabled with [`RetryPolicy::retry_io`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+  max_attempts: u32,
+  initial_delay: Duration,
+  max_delay: Duration,
+  multiplier: u32,
+  retry_io: bool,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts: 5,
+      initial_delay: Duration::from_millis(100),
+      max_delay: Duration::from_secs(2),
+      multiplier: 2,
+      retry_io: false,
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// Policy which never retries
+  pub fn none() -> Self {
+    Self { max_attempts: 1, ..Self::default() }
+  }
+
+  /// Set the maximum number of attempts, including the first one
+  pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+    self.max_attempts = max_attempts.max(1);
+    self
+  }
+
+  /// Set the delay before the first retry
+  pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+    self.initial_delay = initial_delay;
+    self
+  }
+
+  /// Set the maximum delay between two attempts
+  pub fn max_delay(mut self, max_delay: Duration) -> Self {
+    self.max_delay = max_delay;
+    self
+  }
+
+  /// Set the factor the delay is multiplied with after every retry
+  pub fn multiplier(mut self, multiplier: u32) -> Self {
+    self.multiplier = multiplier.max(1);
+    self
+  }
+
+  /// Also retry I/O errors during transfers, disabled by default
+  ///
+  /// An I/O error doesn't tell whether the camera already executed the command, so only enable
+  /// this for policies running idempotent operations like reading files or the configuration.
+  /// Retrying e.g. a capture could take an additional image.
+  ///
+  /// Errors finding, claiming or locking the device are never retried.
+  pub fn retry_io(mut self, retry_io: bool) -> Self {
+    self.retry_io = retry_io;
+    self
+  }
+
+  /// Check if an operation failing with `error` should be retried
+  pub fn should_retry(&self, error: &Error) -> bool {
+    match error.kind() {
+      ErrorKind::CameraBusy => true,
+      ErrorKind::Io
+      | ErrorKind::IoRead
+      | ErrorKind::IoWrite
+      | ErrorKind::IoUpdate
+      | ErrorKind::IoUsbClearHalt => self.retry_io,
+      _ => false,
+    }
+  }
+
+  /// Delay before the retry following attempt number `attempt`, starting at 1
+  fn delay(&self, attempt: u32) -> Duration {
+    let factor = self.multiplier.saturating_pow(attempt.saturating_sub(1));
+    self.initial_delay.saturating_mul(factor).min(self.max_delay)
+  }
+
+  /// Run `operation`, retrying it as long as it fails with a retryable error
+  ///
+  /// Returns the error of the last attempt if all attempts failed.
+  pub fn run<T>(&self, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 1;
+
+    loop {
+      match operation() {
+        Err(error) if attempt < self.max_attempts && self.should_retry(&error) => {
+          log::debug!("Attempt {} failed, retrying: {}", attempt, error);
+          thread::sleep(self.delay(attempt));
+          attempt += 1;
+        }
+        result => return result,
+      }
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::RetryPolicy;
+  use crate::Error;
+  use std::{os::raw::c_int, time::Duration};
+
+  fn error(code: c_int) -> Error {
+    Error::new(code, None)
+  }
+
+  #[test]
+  fn test_delay() {
+    let policy = RetryPolicy::default();
+
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(5), Duration::from_millis(1600));
+    assert_eq!(policy.delay(6), Duration::from_secs(2));
+    assert_eq!(policy.delay(u32::MAX), Duration::from_secs(2));
+
+    let constant = policy.multiplier(1);
+    assert_eq!(constant.delay(10), Duration::from_millis(100));
+  }
+
+  #[test]
+  fn test_should_retry() {
+    let policy = RetryPolicy::default();
+
+    assert!(policy.should_retry(&error(libgphoto2_sys::GP_ERROR_CAMERA_BUSY)));
+    assert!(!policy.should_retry(&error(libgphoto2_sys::GP_ERROR_IO)));
+    assert!(!policy.should_retry(&error(libgphoto2_sys::GP_ERROR_IO_USB_FIND)));
+    assert!(!policy.should_retry(&error(libgphoto2_sys::GP_ERROR_BAD_PARAMETERS)));
+
+    let policy = policy.retry_io(true);
+    assert!(policy.should_retry(&error(libgphoto2_sys::GP_ERROR_IO_READ)));
+    assert!(!policy.should_retry(&error(libgphoto2_sys::GP_ERROR_IO_USB_FIND)));
+  }
+
+  #[test]
+  fn test_run() {
+    let policy = RetryPolicy::default().initial_delay(Duration::ZERO).max_attempts(3);
+
+    let mut attempts = 0;
+    let result = policy.run(|| {
+      attempts += 1;
+      match attempts {
+        1 | 2 => Err(error(libgphoto2_sys::GP_ERROR_CAMERA_BUSY)),
+        _ => Ok(attempts),
+      }
+    });
+    assert_eq!(result.unwrap(), 3);
+
+    let mut attempts = 0;
+    let result = policy.run(|| -> crate::Result<()> {
+      attempts += 1;
+      Err(error(libgphoto2_sys::GP_ERROR_CAMERA_BUSY))
+    });
+    assert!(result.unwrap_err().is_busy());
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let result = policy.run(|| -> crate::Result<()> {
+      attempts += 1;
+      Err(error(libgphoto2_sys::GP_ERROR_IO))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+
+    assert_eq!(RetryPolicy::none().run(|| Ok(1)).unwrap(), 1);
+  }
+}
@@ -0,0 +1,640 @@
+//! Typed access to common camera settings
+//!
+//! Drivers name and format their configuration differently, e.g. the aperture is called
+//! `aperture` on Canon and `f-number` on Nikon cameras. [`Settings`] hides these differences
+//! behind typed getters and setters.
+//!
+//! ## Setting the exposure
+//! ```no_run
+//! use gphoto2::{settings::{Iso, ShutterSpeed}, Context, Result};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//! let settings = camera.settings();
+//!
+//! println!("Available ISOs: {:?}", settings.isos()?);
+//!
+//! settings.set_iso(Iso::Value(400))?;
+//! settings.set_aperture(5.6)?;
+//! settings.set_shutter_speed(ShutterSpeed::Duration(Duration::from_millis(4)))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  widget::{RadioWidget, TextWidget, Widget},
+  Camera, Error, Result,
+};
+use std::{
+  fmt,
+  os::raw::c_int,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const ISO: &[&str] = &["iso", "isospeed"];
+const APERTURE: &[&str] = &["aperture", "f-number"];
+const SHUTTER_SPEED: &[&str] = &["shutterspeed", "shutterspeed2"];
+const WHITE_BALANCE: &[&str] = &["whitebalance"];
+const IMAGE_FORMAT: &[&str] = &["imageformat", "imagequality"];
+const CAPTURE_TARGET: &[&str] = &["capturetarget"];
+const DATETIME: &[&str] = &["datetime", "datetimeutc"];
+const GPS_LATITUDE: &[&str] = &["gpslatitude"];
+const GPS_LONGITUDE: &[&str] = &["gpslongitude"];
+const GPS_ALTITUDE: &[&str] = &["gpsaltitude"];
+const OWNER_NAME: &[&str] = &["ownername"];
+const ARTIST: &[&str] = &["artist"];
+const COPYRIGHT: &[&str] = &["copyright"];
+
+// Conservative limits that fit the Canon and Nikon drivers
+const OWNER_NAME_MAX_LEN: usize = 31;
+const ARTIST_MAX_LEN: usize = 36;
+const COPYRIGHT_MAX_LEN: usize = 54;
+
+/// ISO sensitivity
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Iso {
+  /// Chosen by the camera
+  Auto,
+  /// Fixed ISO value
+  Value(u32),
+}
+
+impl Iso {
+  fn parse(choice: &str) -> Option<Self> {
+    let choice = choice.trim();
+
+    if choice.to_lowercase().contains("auto") {
+      Some(Self::Auto)
+    } else {
+      choice.parse().ok().map(Self::Value)
+    }
+  }
+}
+
+impl fmt::Display for Iso {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Auto => f.write_str("Auto"),
+      Self::Value(iso) => write!(f, "{iso}"),
+    }
+  }
+}
+
+/// Exposure time
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ShutterSpeed {
+  /// Shutter stays open as long as the release is pressed
+  Bulb,
+  /// Fixed exposure time
+  Duration(Duration),
+}
+
+impl ShutterSpeed {
+  fn parse(choice: &str) -> Option<Self> {
+    let choice = choice.trim();
+
+    if choice.eq_ignore_ascii_case("bulb") {
+      return Some(Self::Bulb);
+    }
+
+    let choice = choice.trim_end_matches(['s', '"']);
+
+    let seconds = match choice.split_once('/') {
+      Some((numerator, denominator)) => {
+        numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?
+      }
+      None => choice.parse().ok()?,
+    };
+
+    Duration::try_from_secs_f64(seconds).ok().map(Self::Duration)
+  }
+
+  /// Compare with a tolerance of 1%, since choices like `1/3` are rounded
+  fn matches(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Bulb, Self::Bulb) => true,
+      (Self::Duration(a), Self::Duration(b)) => {
+        (a.as_secs_f64() - b.as_secs_f64()).abs() <= b.as_secs_f64() * 0.01
+      }
+      _ => false,
+    }
+  }
+}
+
+impl fmt::Display for ShutterSpeed {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Bulb => f.write_str("Bulb"),
+      Self::Duration(duration) if duration.as_secs_f64() < 1.0 => {
+        write!(f, "1/{}", (1.0 / duration.as_secs_f64()).round())
+      }
+      Self::Duration(duration) => write!(f, "{}s", duration.as_secs_f64()),
+    }
+  }
+}
+
+/// Where captured images are stored
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CaptureTarget {
+  /// Internal memory of the camera, images are lost if they are not downloaded
+  InternalRam,
+  /// Memory card
+  MemoryCard,
+}
+
+impl CaptureTarget {
+  fn parse(choice: &str) -> Option<Self> {
+    let choice = choice.to_lowercase();
+
+    if choice.contains("ram") {
+      Some(Self::InternalRam)
+    } else if choice.contains("card") {
+      Some(Self::MemoryCard)
+    } else {
+      None
+    }
+  }
+}
+
+fn parse_aperture(choice: &str) -> Option<f32> {
+  let choice = choice.trim();
+  let choice = choice.strip_prefix("f/").or_else(|| choice.strip_prefix('f')).unwrap_or(choice);
+
+  choice.trim().parse().ok()
+}
+
+/// Typed access to the common settings of a camera
+///
+/// Created by [`Camera::settings`]. Every method reads the configuration from the camera
+/// and blocks the current thread until it is done.
+pub struct Settings {
+  camera: Camera,
+}
+
+impl Settings {
+  pub(crate) fn new(camera: Camera) -> Self {
+    Self { camera }
+  }
+
+  /// Get the first of the widgets named `names` that exists on the camera
+  ///
+  /// Other errors (e.g. the camera is busy) are returned without trying the remaining names.
+  fn widget<T: TryFrom<Widget> + 'static + Send>(&self, names: &[&str]) -> Result<T>
+  where
+    Error: From<T::Error>,
+  {
+    let mut last_error = None;
+
+    for name in names {
+      match self.camera.config_key::<T>(name).wait() {
+        Ok(radio) => return Ok(radio),
+        Err(error) if error.is_missing_config() => last_error = Some(error),
+        Err(error) => return Err(error),
+      }
+    }
+
+    Err(last_error.unwrap_or_else(|| Error::bad_parameters("No widget names given")))
+  }
+
+  fn get<T>(&self, names: &[&str], parse: impl Fn(&str) -> Option<T>) -> Result<T> {
+    let radio = self.widget::<RadioWidget>(names)?;
+    let choice = radio.choice();
+
+    parse(&choice).ok_or_else(|| {
+      Error::not_supported(format!("Could not parse {} value {:?}", radio.name(), choice))
+    })
+  }
+
+  fn choices<T>(&self, names: &[&str], parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+    Ok(
+      self
+        .widget::<RadioWidget>(names)?
+        .choices_iter()
+        .filter_map(|choice| parse(&choice))
+        .collect(),
+    )
+  }
+
+  fn set<T>(
+    &self,
+    names: &[&str],
+    parse: impl Fn(&str) -> Option<T>,
+    is_match: impl Fn(&T) -> bool,
+  ) -> Result<()> {
+    let radio = self.widget::<RadioWidget>(names)?;
+    let choice = radio
+      .choices_iter()
+      .find(|choice| parse(choice).is_some_and(|value| is_match(&value)))
+      .ok_or_else(|| Error::bad_parameters(format!("Value not available for {}", radio.name())))?;
+
+    radio.set_choice(&choice)?;
+    self.camera.set_config(&radio).wait()
+  }
+
+  /// Get the ISO sensitivity
+  pub fn iso(&self) -> Result<Iso> {
+    self.get(ISO, Iso::parse)
+  }
+
+  /// Get the available ISO sensitivities
+  pub fn isos(&self) -> Result<Vec<Iso>> {
+    self.choices(ISO, Iso::parse)
+  }
+
+  /// Set the ISO sensitivity
+  pub fn set_iso(&self, iso: Iso) -> Result<()> {
+    self.set(ISO, Iso::parse, |choice| *choice == iso)
+  }
+
+  /// Get the aperture as f-number
+  pub fn aperture(&self) -> Result<f32> {
+    self.get(APERTURE, parse_aperture)
+  }
+
+  /// Get the available apertures as f-numbers
+  pub fn apertures(&self) -> Result<Vec<f32>> {
+    self.choices(APERTURE, parse_aperture)
+  }
+
+  /// Set the aperture as f-number, e.g. `5.6`
+  pub fn set_aperture(&self, aperture: f32) -> Result<()> {
+    self.set(APERTURE, parse_aperture, |choice| (choice - aperture).abs() < 0.05)
+  }
+
+  /// Get the shutter speed
+  pub fn shutter_speed(&self) -> Result<ShutterSpeed> {
+    self.get(SHUTTER_SPEED, ShutterSpeed::parse)
+  }
+
+  /// Get the available shutter speeds
+  pub fn shutter_speeds(&self) -> Result<Vec<ShutterSpeed>> {
+    self.choices(SHUTTER_SPEED, ShutterSpeed::parse)
+  }
+
+  /// Set the shutter speed
+  ///
+  /// The shutter speed must match one of the available speeds within 1%.
+  pub fn set_shutter_speed(&self, shutter_speed: ShutterSpeed) -> Result<()> {
+    self.set(SHUTTER_SPEED, ShutterSpeed::parse, |choice| choice.matches(&shutter_speed))
+  }
+
+  /// Get the white balance, e.g. `Daylight`
+  pub fn white_balance(&self) -> Result<String> {
+    self.get(WHITE_BALANCE, |choice| Some(choice.to_owned()))
+  }
+
+  /// Get the available white balance modes
+  pub fn white_balances(&self) -> Result<Vec<String>> {
+    self.choices(WHITE_BALANCE, |choice| Some(choice.to_owned()))
+  }
+
+  /// Set the white balance
+  pub fn set_white_balance(&self, white_balance: &str) -> Result<()> {
+    self.set(WHITE_BALANCE, |choice| Some(choice.to_owned()), |choice| choice == white_balance)
+  }
+
+  /// Get the image format, e.g. `RAW + Large Fine JPEG`
+  pub fn image_format(&self) -> Result<String> {
+    self.get(IMAGE_FORMAT, |choice| Some(choice.to_owned()))
+  }
+
+  /// Get the available image formats
+  pub fn image_formats(&self) -> Result<Vec<String>> {
+    self.choices(IMAGE_FORMAT, |choice| Some(choice.to_owned()))
+  }
+
+  /// Set the image format
+  pub fn set_image_format(&self, image_format: &str) -> Result<()> {
+    self.set(IMAGE_FORMAT, |choice| Some(choice.to_owned()), |choice| choice == image_format)
+  }
+
+  /// Get where captured images are stored
+  pub fn capture_target(&self) -> Result<CaptureTarget> {
+    self.get(CAPTURE_TARGET, CaptureTarget::parse)
+  }
+
+  /// Set where captured images are stored
+  pub fn set_capture_target(&self, target: CaptureTarget) -> Result<()> {
+    self.set(CAPTURE_TARGET, CaptureTarget::parse, |choice| *choice == target)
+  }
+
+  /// Get the date and time of the camera clock
+  pub fn datetime(&self) -> Result<SystemTime> {
+    match self.widget::<Widget>(DATETIME)? {
+      Widget::Date(date) => Ok(UNIX_EPOCH + Duration::from_secs(u64::try_from(date.timestamp())?)),
+      Widget::Text(text) => {
+        let value = text.value();
+        parse_ptp_datetime(&value)
+          .ok_or_else(|| Error::not_supported(format!("Could not parse date {value:?}")))
+      }
+      widget => Err(Error::not_supported(format!("Unsupported datetime widget: {widget:?}"))),
+    }
+  }
+
+  /// Set the camera clock to `time`
+  ///
+  /// Cameras with a text date configuration get the time as UTC in the PTP
+  /// `YYYYMMDDThhmmssZ` format.
+  pub fn sync_datetime(&self, time: SystemTime) -> Result<()> {
+    let seconds =
+      time.duration_since(UNIX_EPOCH).map_err(|_| "Time is before the UNIX epoch")?.as_secs();
+    let widget = self.widget::<Widget>(DATETIME)?;
+
+    match &widget {
+      Widget::Date(date) => date.set_timestamp(c_int::try_from(seconds)?),
+      Widget::Text(text) => text.set_value(&format_ptp_datetime(seconds))?,
+      _ => return Err(Error::not_supported(format!("Unsupported datetime widget: {widget:?}"))),
+    }
+
+    self.camera.set_config(&widget).wait()
+  }
+
+  /// Set the GPS position written to the metadata of new images
+  ///
+  /// `latitude` and `longitude` are in decimal degrees (north and east are positive),
+  /// `altitude` is in meters. The altitude is skipped if the camera has no `gpsaltitude`
+  /// configuration. Text configurations get the values as decimal numbers.
+  pub fn set_gps(&self, latitude: f64, longitude: f64, altitude: f64) -> Result<()> {
+    if !(-90.0..=90.0).contains(&latitude) {
+      return Err(Error::bad_parameters(format!("Latitude {latitude} out of range")));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+      return Err(Error::bad_parameters(format!("Longitude {longitude} out of range")));
+    }
+
+    let mut widgets = vec![
+      (self.widget::<Widget>(GPS_LATITUDE)?, latitude),
+      (self.widget::<Widget>(GPS_LONGITUDE)?, longitude),
+    ];
+
+    if let Ok(altitude_widget) = self.widget::<Widget>(GPS_ALTITUDE) {
+      widgets.push((altitude_widget, altitude));
+    }
+
+    for (widget, value) in widgets {
+      set_number(&widget, value)?;
+      self.camera.set_config(&widget).wait()?;
+    }
+
+    Ok(())
+  }
+
+  fn set_text(&self, names: &[&str], value: &str, max_len: usize) -> Result<()> {
+    let widget = self.widget::<TextWidget>(names)?;
+
+    if !value.is_ascii() {
+      return Err(Error::bad_parameters(format!(
+        "{} must only contain ASCII characters",
+        widget.name()
+      )));
+    }
+    if value.len() > max_len {
+      return Err(Error::bad_parameters(format!(
+        "{} must be at most {max_len} characters long",
+        widget.name()
+      )));
+    }
+
+    widget.set_value(value)?;
+    self.camera.set_config(&widget).wait()
+  }
+
+  /// Get the owner name
+  pub fn owner_name(&self) -> Result<String> {
+    Ok(self.widget::<TextWidget>(OWNER_NAME)?.value())
+  }
+
+  /// Set the owner name, at most 31 ASCII characters
+  pub fn set_owner_name(&self, owner_name: &str) -> Result<()> {
+    self.set_text(OWNER_NAME, owner_name, OWNER_NAME_MAX_LEN)
+  }
+
+  /// Get the artist written to the metadata of new images
+  pub fn artist(&self) -> Result<String> {
+    Ok(self.widget::<TextWidget>(ARTIST)?.value())
+  }
+
+  /// Set the artist written to the metadata of new images, at most 36 ASCII characters
+  pub fn set_artist(&self, artist: &str) -> Result<()> {
+    self.set_text(ARTIST, artist, ARTIST_MAX_LEN)
+  }
+
+  /// Get the copyright written to the metadata of new images
+  pub fn copyright(&self) -> Result<String> {
+    Ok(self.widget::<TextWidget>(COPYRIGHT)?.value())
+  }
+
+  /// Set the copyright written to the metadata of new images, at most 54 ASCII characters
+  pub fn set_copyright(&self, copyright: &str) -> Result<()> {
+    self.set_text(COPYRIGHT, copyright, COPYRIGHT_MAX_LEN)
+  }
+}
+
+/// Write a number to a text or range widget
+fn set_number(widget: &Widget, value: f64) -> Result<()> {
+  match widget {
+    Widget::Text(text) => text.set_value(&format!("{value:.6}")),
+    #[allow(clippy::as_conversions)]
+    Widget::Range(range) => range.try_set_value(value as f32),
+    _ => Err(Error::not_supported(format!("Unsupported {} widget: {widget:?}", widget.name()))),
+  }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year - era * 400;
+  let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+  era * 146097 + day_of_era - 719468
+}
+
+/// Date of a number of days since 1970-01-01, inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+  let days = days + 719468;
+  let era = days.div_euclid(146097);
+  let day_of_era = days - era * 146097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+  let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+  (year, month, day)
+}
+
+fn format_ptp_datetime(seconds: u64) -> String {
+  let days = seconds / 86400;
+  let seconds_of_day = seconds % 86400;
+  let (year, month, day) = civil_from_days(i64::try_from(days).unwrap_or(i64::MAX));
+
+  format!(
+    "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+    seconds_of_day / 3600,
+    seconds_of_day / 60 % 60,
+    seconds_of_day % 60
+  )
+}
+
+/// Parse a PTP `YYYYMMDDThhmmss[.s][Z|±hhmm]` date
+///
+/// Dates without a time zone are taken as UTC.
+fn parse_ptp_datetime(value: &str) -> Option<SystemTime> {
+  let value = value.trim();
+  let number = |value: &str, range: std::ops::Range<usize>| -> Option<i64> {
+    let digits = value.get(range)?;
+    digits.bytes().all(|byte| byte.is_ascii_digit()).then(|| digits.parse().ok())?
+  };
+  let field = |range| number(value, range);
+
+  if value.get(8..9)? != "T" {
+    return None;
+  }
+
+  let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+  let (hour, minute, second) = (field(9..11)?, field(11..13)?, field(13..15)?);
+
+  if !(1..=12).contains(&month)
+    || !(1..=31).contains(&day)
+    || hour > 23
+    || minute > 59
+    || second > 60
+  {
+    return None;
+  }
+
+  // Tenths of seconds are ignored
+  let mut zone = value.get(15..)?;
+  if let Some(fraction) = zone.strip_prefix('.') {
+    zone = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+  }
+
+  let offset = match zone.as_bytes().first() {
+    None | Some(b'Z') if zone.len() <= 1 => 0,
+    Some(sign @ (b'+' | b'-')) if zone.len() == 5 => {
+      let offset = number(zone, 1..3)? * 3600 + number(zone, 3..5)? * 60;
+      if *sign == b'+' {
+        offset
+      } else {
+        -offset
+      }
+    }
+    _ => return None,
+  };
+
+  let days = days_from_civil(year, month, day);
+  let seconds = days * 86400 + hour * 3600 + minute * 60 + second - offset;
+
+  Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{
+    civil_from_days, days_from_civil, format_ptp_datetime, parse_aperture, parse_ptp_datetime,
+    CaptureTarget, Iso, ShutterSpeed,
+  };
+  use std::time::{Duration, UNIX_EPOCH};
+
+  #[test]
+  fn test_parse_iso() {
+    assert_eq!(Iso::parse("100"), Some(Iso::Value(100)));
+    assert_eq!(Iso::parse(" 25600 "), Some(Iso::Value(25600)));
+    assert_eq!(Iso::parse("Auto ISO"), Some(Iso::Auto));
+    assert_eq!(Iso::parse("Hi 1"), None);
+  }
+
+  #[test]
+  fn test_parse_shutter_speed() {
+    let duration = |seconds| Some(ShutterSpeed::Duration(Duration::from_secs_f64(seconds)));
+
+    assert_eq!(ShutterSpeed::parse("bulb"), Some(ShutterSpeed::Bulb));
+    assert_eq!(ShutterSpeed::parse("Bulb"), Some(ShutterSpeed::Bulb));
+    assert_eq!(ShutterSpeed::parse("1/250"), duration(0.004));
+    assert_eq!(ShutterSpeed::parse("1 / 4"), duration(0.25));
+    assert_eq!(ShutterSpeed::parse("30\""), duration(30.0));
+    assert_eq!(ShutterSpeed::parse("2.5s"), duration(2.5));
+    assert_eq!(ShutterSpeed::parse("0.5"), duration(0.5));
+
+    assert_eq!(ShutterSpeed::parse("Time"), None);
+    assert_eq!(ShutterSpeed::parse("1/0"), None);
+    assert_eq!(ShutterSpeed::parse("-1"), None);
+  }
+
+  #[test]
+  fn test_shutter_speed_matches() {
+    let third = ShutterSpeed::parse("1/3").unwrap();
+    let rounded = ShutterSpeed::parse("0.3").unwrap();
+
+    assert!(!rounded.matches(&third));
+    assert!(ShutterSpeed::parse("0.333").unwrap().matches(&third));
+    assert!(ShutterSpeed::Bulb.matches(&ShutterSpeed::Bulb));
+    assert!(!ShutterSpeed::Bulb.matches(&third));
+
+    assert_eq!(third.to_string(), "1/3");
+    assert_eq!(ShutterSpeed::parse("2.5s").unwrap().to_string(), "2.5s");
+  }
+
+  #[test]
+  fn test_parse_aperture() {
+    assert_eq!(parse_aperture("f/2.8"), Some(2.8));
+    assert_eq!(parse_aperture("f5.6"), Some(5.6));
+    assert_eq!(parse_aperture(" 8 "), Some(8.0));
+    assert_eq!(parse_aperture("implicit auto"), None);
+  }
+
+  #[test]
+  fn test_parse_capture_target() {
+    assert_eq!(CaptureTarget::parse("Internal RAM"), Some(CaptureTarget::InternalRam));
+    assert_eq!(CaptureTarget::parse("Memory card"), Some(CaptureTarget::MemoryCard));
+    assert_eq!(CaptureTarget::parse("Card 2"), Some(CaptureTarget::MemoryCard));
+    assert_eq!(CaptureTarget::parse("Unknown"), None);
+  }
+
+  #[test]
+  fn test_civil_from_days() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    assert_eq!(civil_from_days(19783), (2024, 3, 1));
+
+    for days in (-1_000_000..1_000_000).step_by(997) {
+      let (year, month, day) = civil_from_days(days);
+      assert_eq!(days_from_civil(year, month, day), days);
+    }
+  }
+
+  #[test]
+  fn test_format_ptp_datetime() {
+    assert_eq!(format_ptp_datetime(0), "19700101T000000Z");
+    assert_eq!(format_ptp_datetime(1_709_210_096), "20240229T123456Z");
+  }
+
+  #[test]
+  fn test_parse_ptp_datetime() {
+    let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+
+    assert_eq!(parse_ptp_datetime("20240229T123456"), Some(time));
+    assert_eq!(parse_ptp_datetime("20240229T123456Z"), Some(time));
+    assert_eq!(parse_ptp_datetime("20240229T123456.7Z"), Some(time));
+    assert_eq!(parse_ptp_datetime(" 20240229T143456+0200 "), Some(time));
+    assert_eq!(parse_ptp_datetime("20240229T093456-0300"), Some(time));
+    assert_eq!(parse_ptp_datetime(&format_ptp_datetime(1_709_210_096)), Some(time));
+
+    assert_eq!(parse_ptp_datetime("20240229 123456"), None);
+    assert_eq!(parse_ptp_datetime("20241329T123456"), None);
+    assert_eq!(parse_ptp_datetime("20240229T12345"), None);
+    assert_eq!(parse_ptp_datetime("20240229T123456+02"), None);
+    assert_eq!(parse_ptp_datetime("20240229T123456X"), None);
+    assert_eq!(parse_ptp_datetime("2024-2-29T123456"), None);
+  }
+}
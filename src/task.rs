@@ -2,8 +2,9 @@
 
 use crate::{
   context::{CancelHandler, ProgressHandler},
+  error,
   thread::{TaskFunc, ThreadManager, THREAD_MANAGER},
-  Context,
+  Camera, Context, Error,
 };
 use crossbeam_channel::{bounded, Receiver, RecvError, Sender};
 use std::{
@@ -14,14 +15,39 @@ use std::{
     Arc,
   },
   task::{Poll, Waker},
+  thread,
 };
 
-type ToBeRunTask<T> = Option<(Box<dyn FnOnce() -> T + Send>, Sender<T>)>;
+type ToBeRunTask<T> = Option<(Box<dyn FnOnce(&CancellationToken) -> T + Send>, Sender<T>)>;
 
 #[derive(Clone, Copy)]
 pub(crate) struct BackgroundPtr<T>(pub *mut T);
 
 /// Allows awaiting (or blocking) libgphoto2 function responses
+///
+/// All libgphoto2 calls are executed on a dedicated background thread, so a
+/// [`Task`] never blocks the thread it was created on until it is either
+/// [waited](Task::wait) for or awaited.
+///
+/// ## Async usage
+///
+/// [`Task`] implements [`Future`], so it can be awaited in any async runtime
+/// without wrapping the call in `spawn_blocking`:
+///
+/// ```no_run
+/// use gphoto2::{Context, Result};
+///
+/// async fn capture() -> Result<()> {
+///   let context = Context::new()?;
+///   let camera = context.autodetect_camera().await?;
+///
+///   let file = camera.capture_image().await?;
+///   let data = camera.fs().download(&file.folder(), &file.name()).await?;
+///   println!("Downloaded {} ({})", data.name(), data.mime_type());
+///
+///   Ok(())
+/// }
+/// ```
 pub struct Task<T> {
   rx: Receiver<T>,
   cancel: Arc<AtomicBool>,
@@ -29,18 +55,83 @@ pub struct Task<T> {
   waker_set: bool,
   task: ToBeRunTask<T>,
   context: Option<BackgroundPtr<libgphoto2_sys::GPContext>>,
+  camera: Option<Arc<str>>,
   progress_handler: Option<Box<dyn ProgressHandler>>,
   recv_waker: Option<Receiver<Waker>>,
+  /// Run on a dedicated thread instead of the libgphoto2 thread,
+  /// converting the error if the thread can't be spawned
+  blocking: Option<fn(std::io::Error) -> T>,
 }
 
 struct TaskCancelHandler(Arc<AtomicBool>);
 
+/// Handle to cancel a [`Task`] from another thread
+///
+/// Created by [`Task::cancel_token`]. Cancelling a running task makes libgphoto2 abort the
+/// operation at the next opportunity, the task then returns an error of kind
+/// [`ErrorKind::Cancelled`](crate::error::ErrorKind::Cancelled).
+///
+/// Tasks made of several operations, like [`Camera::capture_burst`] or
+/// [`Camera::wait_for_file`], pass the cancellation on to the operation currently running
+/// and don't start the next one.
+///
+/// ## Example
+///
+/// ```no_run
+/// use gphoto2::{Context, Result};
+/// use std::{thread, time::Duration};
+///
+/// # fn main() -> Result<()> {
+/// let camera = Context::new()?.autodetect_camera().wait()?;
+///
+/// let task = camera.fs().download("/store_00010001/DCIM/100CANON", "MVI_0001.MOV");
+/// let cancel_token = task.cancel_token();
+///
+/// thread::spawn(move || {
+///   thread::sleep(Duration::from_secs(5));
+///   cancel_token.cancel();
+/// });
+///
+/// let result = task.wait();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+  /// Request the task to be cancelled
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  /// Check if cancellation of the task was requested
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+
+  /// Return an error of kind [`ErrorKind::Cancelled`](crate::error::ErrorKind::Cancelled)
+  /// if cancellation was requested
+  pub(crate) fn check(&self) -> crate::Result<()> {
+    if self.is_cancelled() {
+      return Err(Error::new(libgphoto2_sys::GP_ERROR_CANCEL, None));
+    }
+
+    Ok(())
+  }
+}
+
 impl<T> Task<T>
 where
   T: 'static + Send,
 {
   /// Starts a new task
   pub(crate) unsafe fn new(fun: impl FnOnce() -> T + 'static + Send) -> Self {
+    Self::with_token(move |_| fun())
+  }
+
+  /// Starts a new task, passing the [`CancellationToken`] of the task to `fun`
+  unsafe fn with_token(fun: impl FnOnce(&CancellationToken) -> T + 'static + Send) -> Self {
     ThreadManager::ensure_started();
 
     let (tx, rx) = bounded(1);
@@ -54,7 +145,9 @@ where
       waker_set: false,
       task: Some((Box::new(fun), tx)),
       context: None,
+      camera: None,
       progress_handler: None,
+      blocking: None,
     }
   }
 
@@ -64,12 +157,21 @@ where
     self
   }
 
+  /// Attribute errors of the task to `camera`
+  pub(crate) fn for_camera(mut self, camera: &Camera) -> Self {
+    self.camera = Some(camera.identity());
+
+    self
+  }
+
   fn start_task(&mut self) {
     if let Some((fun, tx)) = self.task.take() {
       let mut opt_context_ptr = self.context.take();
+      let camera = self.camera.take();
       let recv_waker = self.recv_waker.take();
       let progress_handler = self.progress_handler.take();
       let cancel = self.cancel.clone();
+      let tx_spawn_error = tx.clone();
 
       #[allow(unused_must_use)]
       let task: TaskFunc = Box::new(move || {
@@ -78,7 +180,7 @@ where
         if let Some(context_ptr) = opt_context_ptr.as_mut() {
           let mut task_context = Context::from_ptr(*context_ptr);
 
-          let cancel_handler = TaskCancelHandler(cancel);
+          let cancel_handler = TaskCancelHandler(cancel.clone());
           task_context.set_cancel_handler(cancel_handler);
 
           if let Some(progress_handler) = progress_handler {
@@ -88,7 +190,7 @@ where
           context = Some(task_context);
         }
 
-        let result = fun();
+        let result = error::with_camera(camera, || fun(&CancellationToken(cancel)));
 
         if let Some(context) = context.as_mut() {
           context.unset_cancel_handlers();
@@ -101,7 +203,12 @@ where
         }
       });
 
-      if let Some(manager) = THREAD_MANAGER.read().unwrap().as_ref() {
+      if let Some(spawn_error) = self.blocking {
+        if let Err(error) = thread::Builder::new().name("gphoto2-blocking".to_owned()).spawn(task) {
+          log::error!("Could not spawn a thread for a blocking task: {}", error);
+          tx_spawn_error.send(spawn_error(error)).ok();
+        }
+      } else if let Some(manager) = THREAD_MANAGER.read().unwrap().as_ref() {
         manager.spawn_task(task);
       }
     }
@@ -144,12 +251,50 @@ where
     self.cancel.store(true, Ordering::Relaxed);
   }
 
+  /// Get a [`CancellationToken`] which can cancel this task from another thread,
+  /// even while the task is being waited on
+  pub fn cancel_token(&self) -> CancellationToken {
+    CancellationToken(self.cancel.clone())
+  }
+
   /// Starts the task in background
   pub(crate) fn background(&mut self) {
     self.start_task();
   }
 }
 
+impl<T> Task<crate::Result<T>>
+where
+  T: 'static + Send,
+{
+  /// Starts a new task which runs `fun` on a dedicated thread
+  ///
+  /// Used for operations made of several libgphoto2 calls or waiting between them,
+  /// `fun` may wait for other tasks without stalling the libgphoto2 thread. It gets the
+  /// [`CancellationToken`] of the task and should wait for other tasks with
+  /// [`Task::wait_with`], so cancelling this task cancels them as well.
+  pub(crate) fn blocking(
+    fun: impl FnOnce(&CancellationToken) -> crate::Result<T> + 'static + Send,
+  ) -> Self {
+    // Safety: `fun` only calls libgphoto2 through other tasks
+    let mut task = unsafe { Self::with_token(fun) };
+    task.blocking = Some(|error| Err(error.into()));
+    task
+  }
+
+  /// Block until the result is available as a step of a [blocking](Task::blocking) task
+  ///
+  /// Returns an error of kind [`ErrorKind::Cancelled`](crate::error::ErrorKind::Cancelled)
+  /// without starting this task if `token` was already cancelled, otherwise this task is
+  /// cancelled together with `token`.
+  pub(crate) fn wait_with(mut self, token: &CancellationToken) -> crate::Result<T> {
+    token.check()?;
+
+    self.cancel = token.0.clone();
+    self.wait()
+  }
+}
+
 impl<T> Future for Task<T>
 where
   T: 'static + Send,
@@ -193,3 +338,65 @@ impl<T> Deref for BackgroundPtr<T> {
 unsafe impl<T> Send for BackgroundPtr<T> {}
 unsafe impl<T> Sync for BackgroundPtr<T> {}
 impl<T> Unpin for Task<T> {}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::{CancellationToken, Task};
+  use std::{
+    sync::{
+      atomic::{AtomicBool, Ordering},
+      Arc,
+    },
+    thread,
+    time::Duration,
+  };
+
+  fn wait_for_cancel(token: &CancellationToken) -> crate::Result<()> {
+    loop {
+      token.check()?;
+      thread::sleep(Duration::from_millis(10));
+    }
+  }
+
+  #[test]
+  fn test_blocking_cancel() {
+    let task = Task::blocking(wait_for_cancel);
+    let token = task.cancel_token();
+
+    thread::spawn(move || {
+      thread::sleep(Duration::from_millis(50));
+      token.cancel();
+    });
+
+    assert!(task.wait().unwrap_err().is_cancelled());
+  }
+
+  #[test]
+  fn test_blocking_cancel_inner() {
+    let task = Task::blocking(|token| Task::blocking(wait_for_cancel).wait_with(token));
+    let token = task.cancel_token();
+
+    thread::spawn(move || {
+      thread::sleep(Duration::from_millis(50));
+      token.cancel();
+    });
+
+    assert!(task.wait().unwrap_err().is_cancelled());
+  }
+
+  #[test]
+  fn test_wait_with_cancelled() {
+    let started = Arc::new(AtomicBool::new(false));
+    let inner_started = started.clone();
+
+    let token = CancellationToken(Arc::new(AtomicBool::new(true)));
+    let result = Task::blocking(move |_| {
+      inner_started.store(true, Ordering::SeqCst);
+      Ok(())
+    })
+    .wait_with(&token);
+
+    assert!(result.unwrap_err().is_cancelled());
+    assert!(!started.load(Ordering::SeqCst));
+  }
+}
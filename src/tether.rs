@@ -0,0 +1,212 @@
+//! Tethered shooting
+//!
+//! A [`TetherSession`] waits for new files on the camera (e.g. when the shutter button
+//! is pressed), downloads them to a local directory and hands them to a callback.
+//!
+//! ## Downloading every new image
+//! ```no_run
+//! use gphoto2::{tether::TetherSession, Context, Result};
+//! use std::ops::ControlFlow;
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let camera = context.autodetect_camera().wait()?;
+//!
+//! TetherSession::new(&camera, "captures")
+//!   .name_template("shot-{n}.{ext}")
+//!   .delete_from_camera(true)
+//!   .run(|file| {
+//!     match file {
+//!       Some(Ok(file)) => println!("Saved {}", file.local_path.display()),
+//!       Some(Err(error)) => eprintln!("Download failed: {}", error),
+//!       // No new file within the event timeout, a chance to stop the session
+//!       None => {}
+//!     }
+//!
+//!     ControlFlow::Continue(())
+//!   })?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+  camera::CameraEvent,
+  file::{render_name_template, CameraFilePath},
+  Camera, Error, Result,
+};
+use std::{
+  ops::ControlFlow,
+  path::{Component, Path, PathBuf},
+  time::Duration,
+};
+
+/// File downloaded by a [`TetherSession`]
+#[derive(Debug)]
+pub struct TetheredFile {
+  /// Path of the file on the camera
+  pub camera_path: CameraFilePath,
+  /// Path the file was downloaded to
+  pub local_path: PathBuf,
+}
+
+/// Downloads new files from a camera as they are captured
+///
+/// The local file names are generated from a template, which may contain these placeholders:
+///  - `{name}`: Name of the file on the camera, e.g. `DSC_0001.JPG`
+///  - `{stem}`: Name without extension, e.g. `DSC_0001`
+///  - `{ext}`: Extension of the file, e.g. `JPG`
+///  - `{n}`: Number of the file in this session, starting at 1 and padded to 4 digits
+///
+/// The default template is `{name}`. The rendered name must be a plain file name without path
+/// separators or `..`. Existing local files are never replaced, downloading a file whose local
+/// name already exists fails with an error of kind
+/// [`ErrorKind::FileExists`](crate::error::ErrorKind::FileExists) and the file is kept on the
+/// camera. This can happen with the default template if the file counter of the camera rolls
+/// over or files of different folders have the same name.
+pub struct TetherSession {
+  camera: Camera,
+  destination: PathBuf,
+  name_template: String,
+  delete_from_camera: bool,
+  event_timeout: Duration,
+  count: u64,
+}
+
+impl TetherSession {
+  /// Create a session downloading files of `camera` to the `destination` directory
+  ///
+  /// The directory is created if it doesn't exist.
+  pub fn new(camera: &Camera, destination: impl Into<PathBuf>) -> Self {
+    Self {
+      camera: camera.clone(),
+      destination: destination.into(),
+      name_template: "{name}".to_owned(),
+      delete_from_camera: false,
+      event_timeout: Duration::from_secs(1),
+      count: 0,
+    }
+  }
+
+  /// Set the template for the local file names
+  pub fn name_template(mut self, template: impl Into<String>) -> Self {
+    self.name_template = template.into();
+    self
+  }
+
+  /// Delete files from the camera after they were downloaded
+  ///
+  /// Files are moved with [`CameraFilePath::take`], so they are only deleted once the
+  /// local copy was synced to disk.
+  pub fn delete_from_camera(mut self, delete: bool) -> Self {
+    self.delete_from_camera = delete;
+    self
+  }
+
+  /// Set the timeout for a single wait on camera events
+  ///
+  /// [`TetherSession::next_file`] returns `None` and the callback of [`TetherSession::run`]
+  /// is called with `None` when no file arrived within this time.
+  pub fn event_timeout(mut self, timeout: Duration) -> Self {
+    self.event_timeout = timeout;
+    self
+  }
+
+  /// Number of files downloaded in this session
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+
+  /// Wait for the next camera event and download the file if one was added
+  ///
+  /// Returns `None` if the event was not a new file.
+  pub fn next_file(&mut self) -> Result<Option<TetheredFile>> {
+    match self.camera.wait_event(self.event_timeout).wait()? {
+      CameraEvent::NewFile(camera_path) => self.download(camera_path).map(Some),
+      _ => Ok(None),
+    }
+  }
+
+  /// Download new files until the callback returns [`ControlFlow::Break`]
+  ///
+  /// The callback is called with every downloaded file, or with `None` if no file was added
+  /// within the [event timeout](TetherSession::event_timeout), so an idle session can be
+  /// stopped as well. Errors while downloading or deleting a file are passed to the callback,
+  /// errors while waiting for events stop the session.
+  pub fn run(
+    &mut self,
+    mut on_file: impl FnMut(Option<Result<TetheredFile>>) -> ControlFlow<()>,
+  ) -> Result<()> {
+    loop {
+      let file = match self.camera.wait_event(self.event_timeout).wait()? {
+        CameraEvent::NewFile(camera_path) => Some(self.download(camera_path)),
+        CameraEvent::Timeout => None,
+        _ => continue,
+      };
+
+      if on_file(file).is_break() {
+        return Ok(());
+      }
+    }
+  }
+
+  fn download(&mut self, camera_path: CameraFilePath) -> Result<TetheredFile> {
+    let local_path = self.destination.join(self.file_name(&camera_path.name())?);
+
+    let local_path = if self.delete_from_camera {
+      camera_path.take(&self.camera, local_path)?
+    } else {
+      camera_path.download_to(&self.camera, local_path)?
+    };
+
+    self.count += 1;
+
+    Ok(TetheredFile { camera_path, local_path })
+  }
+
+  fn file_name(&self, name: &str) -> Result<String> {
+    let file_name = render_name_template(&self.name_template, name)
+      .replace("{n}", &format!("{:04}", self.count + 1));
+
+    let mut components = Path::new(&file_name).components();
+
+    match (components.next(), components.next()) {
+      (Some(Component::Normal(_)), None) if !file_name.contains(std::path::is_separator) => {
+        Ok(file_name)
+      }
+      _ => Err(Error::bad_parameters(format!("{file_name} is not a valid local file name"))),
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::TetherSession;
+  use crate::{error::ErrorKind, file::CameraFilePath};
+  use std::fs;
+
+  #[test]
+  fn test_file_name() {
+    let camera = crate::sample_camera();
+    let session = TetherSession::new(&camera, "captures").name_template("shot-{n}.{ext}");
+    assert_eq!(session.file_name("DSC_0001.JPG").unwrap(), "shot-0001.JPG");
+
+    for template in ["../{name}", "raw/{name}", "/{name}", ".."] {
+      let session = TetherSession::new(&camera, "captures").name_template(template);
+      assert_eq!(session.file_name("DSC_0001.JPG").unwrap_err().kind(), ErrorKind::BadParameters);
+    }
+  }
+
+  #[test]
+  fn test_download_keeps_existing_file() {
+    let camera = crate::sample_camera();
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("blank.jpg"), "existing").unwrap();
+
+    let mut session = TetherSession::new(&camera, temp_dir.path());
+    let file = CameraFilePath::new("/store_00010001/foo", "blank.jpg").unwrap();
+
+    assert_eq!(session.download(file).unwrap_err().kind(), ErrorKind::FileExists);
+    assert_eq!(fs::read(temp_dir.path().join("blank.jpg")).unwrap(), b"existing");
+    assert_eq!(session.count(), 0);
+  }
+}
@@ -0,0 +1,283 @@
+//! Intervalometer
+//!
+//! A [`Timelapse`] captures images on a fixed schedule, optionally downloading them
+//! in the background while the next frames are captured.
+//!
+//! ## Capturing a timelapse
+//! ```no_run
+//! use gphoto2::{Context, Result};
+//! use std::{ops::ControlFlow, time::Duration};
+//!
+//! # fn main() -> Result<()> {
+//! let context = Context::new()?;
+//! let camera = context.autodetect_camera().wait()?;
+//!
+//! camera.timelapse(Duration::from_secs(10)).count(360).download_to("timelapse").run(|frame| {
+//!   match frame {
+//!     Ok(frame) => println!("Frame {} saved to {:?}", frame.index, frame.local_path),
+//!     Err(error) => eprintln!("Frame failed: {}", error),
+//!   }
+//!
+//!   ControlFlow::Continue(())
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{file::CameraFilePath, Camera, Result};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+  fs,
+  ops::ControlFlow,
+  path::PathBuf,
+  thread,
+  time::{Duration, Instant},
+};
+
+/// Single frame of a [`Timelapse`]
+#[derive(Debug)]
+pub struct TimelapseFrame {
+  /// Number of the slot the frame was captured in, starting at 0
+  ///
+  /// Slots missed because a previous capture took too long are skipped, so the indices
+  /// are not always consecutive.
+  pub index: u64,
+  /// Path of the file on the camera
+  pub camera_path: CameraFilePath,
+  /// Path the file was downloaded to, if downloads are enabled
+  pub local_path: Option<PathBuf>,
+}
+
+/// Captures images at a fixed interval
+///
+/// Captures are scheduled in slots relative to the start of the timelapse, so the time a capture
+/// takes does not add up over the frames. If a capture takes longer than the interval, the slots
+/// that started during the capture are skipped and the next capture waits for the following slot,
+/// instead of catching up with captures in quick succession.
+///
+/// Created by [`Camera::timelapse`].
+pub struct Timelapse {
+  camera: Camera,
+  interval: Duration,
+  count: Option<u64>,
+  destination: Option<PathBuf>,
+  delete_from_camera: bool,
+}
+
+impl Timelapse {
+  /// Create a timelapse capturing every `interval`
+  pub fn new(camera: &Camera, interval: Duration) -> Self {
+    Self {
+      camera: camera.clone(),
+      interval,
+      count: None,
+      destination: None,
+      delete_from_camera: false,
+    }
+  }
+
+  /// Stop after `count` slots
+  ///
+  /// Skipped slots count towards `count`, so the timelapse always ends after `count` intervals.
+  /// By default the timelapse runs until the callback returns [`ControlFlow::Break`].
+  pub fn count(mut self, count: u64) -> Self {
+    self.count = Some(count);
+    self
+  }
+
+  /// Download frames to a directory in the background
+  ///
+  /// The files are named after the frame index and their name on the camera,
+  /// e.g. `00042-DSC_0123.JPG`. The directory is created if it doesn't exist.
+  /// Existing files are never replaced, a frame whose file already exists (e.g. from a
+  /// previous run into the same directory) fails with an error of kind
+  /// [`ErrorKind::FileExists`](crate::error::ErrorKind::FileExists) and stays on the camera.
+  pub fn download_to(mut self, destination: impl Into<PathBuf>) -> Self {
+    self.destination = Some(destination.into());
+    self
+  }
+
+  /// Delete frames from the camera after they were downloaded
+  ///
+  /// Frames are moved with [`CameraFilePath::take`], so they are only deleted once the local
+  /// copy was synced to disk. Only has an effect if downloads are enabled with
+  /// [`Timelapse::download_to`].
+  pub fn delete_from_camera(mut self, delete: bool) -> Self {
+    self.delete_from_camera = delete;
+    self
+  }
+
+  /// Run the timelapse, calling `on_frame` for every frame
+  ///
+  /// The callback is called from the current thread once a frame is captured and,
+  /// if enabled, downloaded. Returning [`ControlFlow::Break`] stops the timelapse after
+  /// pending downloads are finished.
+  pub fn run(
+    self,
+    mut on_frame: impl FnMut(Result<TimelapseFrame>) -> ControlFlow<()>,
+  ) -> Result<()> {
+    if let Some(destination) = &self.destination {
+      fs::create_dir_all(destination)?;
+    }
+
+    let (frame_sender, frames) = crossbeam_channel::unbounded();
+    let (capture_sender, downloader) = match self.destination.clone() {
+      Some(destination) => {
+        let (capture_sender, captures) = crossbeam_channel::unbounded();
+        let camera = self.camera.clone();
+        let frame_sender = frame_sender.clone();
+        let delete_from_camera = self.delete_from_camera;
+
+        let handle =
+          thread::Builder::new().name("gphoto2-timelapse".to_owned()).spawn(move || {
+            download_worker(camera, destination, delete_from_camera, captures, frame_sender)
+          })?;
+
+        (Some(capture_sender), Some(handle))
+      }
+      None => (None, None),
+    };
+
+    let start = Instant::now();
+    let count = self.count.unwrap_or(u64::MAX);
+    let mut stopped = false;
+    let mut index = 0;
+
+    while index < count {
+      // Schedule relative to the start, so capture latency doesn't accumulate
+      let scheduled_at = start + self.interval.saturating_mul(index.try_into().unwrap_or(u32::MAX));
+      stopped = deliver_until(&frames, Some(scheduled_at), &mut on_frame);
+
+      if stopped {
+        break;
+      }
+
+      let frame = self.camera.capture_image().wait().map(|camera_path| (index, camera_path));
+
+      match (frame, &capture_sender) {
+        (Ok(frame), Some(capture_sender)) => {
+          // The worker only stops if the channel is closed, so this can't fail
+          _ = capture_sender.send(frame);
+        }
+        (frame, _) => {
+          _ = frame_sender.send(frame.map(|(index, camera_path)| TimelapseFrame {
+            index,
+            camera_path,
+            local_path: None,
+          }));
+        }
+      }
+
+      let next = next_slot(index + 1, start.elapsed(), self.interval);
+      if next > index + 1 {
+        log::warn!(
+          "Frame {} took longer than the interval, skipping {} slots",
+          index,
+          next - index - 1
+        );
+      }
+      index = next;
+    }
+
+    // Finish pending downloads
+    drop(capture_sender);
+    drop(frame_sender);
+
+    if !stopped {
+      deliver_until(&frames, None, &mut on_frame);
+    }
+
+    if let Some(downloader) = downloader {
+      _ = downloader.join();
+    }
+
+    Ok(())
+  }
+}
+
+/// First slot starting at or after `elapsed`, but not before the `due` slot
+///
+/// Slots that already started are skipped, so a late capture never leads to captures in quick succession.
+fn next_slot(due: u64, elapsed: Duration, interval: Duration) -> u64 {
+  let interval = interval.as_nanos();
+
+  if interval == 0 {
+    return due;
+  }
+
+  let started = elapsed.as_nanos().div_ceil(interval);
+  due.max(started.try_into().unwrap_or(u64::MAX))
+}
+
+/// Pass finished frames to the callback until the deadline, returns `true` if the callback requested to stop
+fn deliver_until(
+  frames: &Receiver<Result<TimelapseFrame>>,
+  deadline: Option<Instant>,
+  on_frame: &mut impl FnMut(Result<TimelapseFrame>) -> ControlFlow<()>,
+) -> bool {
+  loop {
+    let frame = match deadline {
+      Some(deadline) => frames.recv_deadline(deadline).ok(),
+      None => frames.recv().ok(),
+    };
+
+    let Some(frame) = frame else { return false };
+
+    if on_frame(frame).is_break() {
+      return true;
+    }
+  }
+}
+
+fn download_worker(
+  camera: Camera,
+  destination: PathBuf,
+  delete_from_camera: bool,
+  captures: Receiver<(u64, CameraFilePath)>,
+  frames: Sender<Result<TimelapseFrame>>,
+) {
+  for (index, camera_path) in captures {
+    let download = || -> Result<TimelapseFrame> {
+      let local_path = destination.join(format!("{index:05}-{}", camera_path.name()));
+
+      let local_path = if delete_from_camera {
+        camera_path.take(&camera, local_path)?
+      } else {
+        camera_path.download_to(&camera, local_path)?
+      };
+
+      Ok(TimelapseFrame { index, camera_path, local_path: Some(local_path) })
+    };
+
+    if frames.send(download()).is_err() {
+      break;
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::next_slot;
+  use std::time::Duration;
+
+  const INTERVAL: Duration = Duration::from_secs(10);
+
+  #[test]
+  fn test_next_slot_on_time() {
+    assert_eq!(next_slot(1, Duration::from_secs(3), INTERVAL), 1);
+    assert_eq!(next_slot(1, INTERVAL, INTERVAL), 1);
+    assert_eq!(next_slot(5, Duration::ZERO, INTERVAL), 5);
+  }
+
+  #[test]
+  fn test_next_slot_skips_missed() {
+    assert_eq!(next_slot(1, Duration::from_millis(10_001), INTERVAL), 2);
+    assert_eq!(next_slot(1, Duration::from_secs(35), INTERVAL), 4);
+    assert_eq!(next_slot(4, Duration::from_secs(40), INTERVAL), 4);
+  }
+
+  #[test]
+  fn test_next_slot_zero_interval() {
+    assert_eq!(next_slot(3, Duration::from_secs(100), Duration::ZERO), 3);
+  }
+}
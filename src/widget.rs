@@ -24,6 +24,7 @@ use std::{
   ffi, fmt,
   ops::{Range, RangeInclusive},
   os::raw::{c_char, c_int, c_void},
+  time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 /// Iterator over the children of a widget
@@ -94,6 +95,33 @@ impl WidgetBase {
     readonly == 1
   }
 
+  /// Returns a descriptive error if the widget is read-only
+  pub(crate) fn check_writable(&self) -> Result<()> {
+    if self.readonly() {
+      return Err(Error::not_supported(format!("Widget {} is read-only", self.name())));
+    }
+
+    Ok(())
+  }
+
+  /// Check if the value of the widget was changed
+  ///
+  /// Widgets are marked as changed when their value is set, only changed widgets
+  /// are written to the camera by [`Camera::set_all_config`].
+  pub fn changed(&self) -> bool {
+    // gp_widget_changed also clears the flag, so it has to be restored
+    try_gp_internal!(let changed = gp_widget_changed(*self.inner).unwrap());
+    if changed == 1 {
+      self.set_changed(true);
+    }
+    changed == 1
+  }
+
+  /// Mark the widget as changed or unchanged
+  pub fn set_changed(&self, changed: bool) {
+    try_gp_internal!(gp_widget_set_changed(*self.inner, changed.into()).unwrap());
+  }
+
   /// Get the widget label
   pub fn label(&self) -> String {
     try_gp_internal!(gp_widget_get_label(*self.inner, &out label).unwrap());
@@ -164,7 +192,24 @@ macro_rules! typed_widgets {
       )*
     }
 
+    /// Type of a [`Widget`].
+    #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum WidgetType {
+      $(
+        #[doc = concat!("Type of a [`", stringify!($name), "`].")]
+        $variant,
+      )*
+    }
+
     impl Widget {
+      /// Get the type of the widget.
+      pub fn widget_type(&self) -> WidgetType {
+        match self {
+          $(Self::$variant(_) => WidgetType::$variant,)*
+        }
+      }
+
       pub(crate) fn new_owned(widget: BackgroundPtr<libgphoto2_sys::CameraWidget>) -> Self {
         let inner = WidgetBase { inner: widget };
 
@@ -187,7 +232,11 @@ macro_rules! typed_widgets {
         fn try_from(widget: Widget) -> Result<Self> {
           match widget {
             Widget::$variant(widget) => Ok(widget),
-            _ => Err(Error::from(format!("Expected {} but got {:?}", stringify!($name), widget))),
+            _ => Err(Error::bad_parameters(format!(
+              "Expected {} but got {:?}",
+              stringify!($name),
+              widget
+            ))),
           }
         }
       }
@@ -270,6 +319,22 @@ typed_widgets!(
   DateWidget, Date = GP_WIDGET_DATE;
 );
 
+/// Serializes the widget and its children as a [`ConfigNode`](crate::config::ConfigNode).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Widget {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::config::ConfigNode::from(self), serializer)
+  }
+}
+
+/// Serializes the widget and its children as a [`ConfigNode`](crate::config::ConfigNode).
+#[cfg(feature = "serde")]
+impl serde::Serialize for GroupWidget {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::config::ConfigNode::from(self), serializer)
+  }
+}
+
 /// Helper that prints `[_; count]` when using `{:?}` or the given list when using `{:#?}`.
 struct MaybeListFmt<F>(F);
 
@@ -328,6 +393,34 @@ impl GroupWidget {
     Ok(Widget::new_shared(BackgroundPtr(child)))
   }
 
+  /// Get a descendant by its path of widget names, e.g. `/main/imgsettings/iso`
+  ///
+  /// The path may start with the name of this widget (e.g. `main` for the root configuration),
+  /// otherwise it is relative to this widget.
+  pub fn get_child_by_path(&self, path: &str) -> Result<Widget> {
+    let mut names = path.split('/').filter(|name| !name.is_empty()).peekable();
+
+    if names.peek() == Some(&self.name().as_str()) {
+      names.next();
+    }
+
+    let mut widget = Widget::Group(self.clone());
+
+    for name in names {
+      widget = match widget {
+        Widget::Group(group) => group.get_child_by_name(name)?,
+        _ => {
+          return Err(Error::bad_parameters(format!(
+            "{} in path {path:?} is not a section",
+            widget.name()
+          )))
+        }
+      };
+    }
+
+    Ok(widget)
+  }
+
   fn fmt_fields(&self, f: &mut fmt::DebugStruct) {
     f.field("children", &MaybeListFmt(|| self.children_iter()));
   }
@@ -340,7 +433,10 @@ impl TextWidget {
   }
 
   /// Set the value of the widget.
+  ///
+  /// Returns an error if the widget is [read-only](WidgetBase::readonly).
   pub fn set_value(&self, value: &str) -> Result<()> {
+    self.check_writable()?;
     unsafe {
       self.set_raw_value::<c_char>(to_c_string!(value));
     }
@@ -359,16 +455,46 @@ impl RangeWidget {
   }
 
   /// Set the value of the widget.
+  ///
+  /// Doesn't check if the widget is [read-only](WidgetBase::readonly),
+  /// see [`RangeWidget::try_set_value`].
   pub fn set_value(&self, value: f32) {
     unsafe { self.set_raw_value::<f32>(&value) }
   }
 
+  /// Set the value of the widget.
+  ///
+  /// Returns an error if the widget is [read-only](WidgetBase::readonly).
+  pub fn try_set_value(&self, value: f32) -> Result<()> {
+    self.check_writable()?;
+    self.set_value(value);
+    Ok(())
+  }
+
   /// Get the range and increment step of the widget.
   pub fn range_and_step(&self) -> (RangeInclusive<f32>, f32) {
     try_gp_internal!(gp_widget_get_range(self.as_ptr(), &out min, &out max, &out step).unwrap());
     (min..=max, step)
   }
 
+  /// Increase the value by one step, clamped to the range. Returns the new value.
+  pub fn increment(&self) -> f32 {
+    let (range, step) = self.range_and_step();
+    self.set_clamped(self.value() + step, range)
+  }
+
+  /// Decrease the value by one step, clamped to the range. Returns the new value.
+  pub fn decrement(&self) -> f32 {
+    let (range, step) = self.range_and_step();
+    self.set_clamped(self.value() - step, range)
+  }
+
+  fn set_clamped(&self, value: f32, range: RangeInclusive<f32>) -> f32 {
+    let value = value.clamp(*range.start(), *range.end());
+    self.set_value(value);
+    value
+  }
+
   fn fmt_fields(&self, f: &mut fmt::DebugStruct) {
     let (range, step) = self.range_and_step();
     f.field("range", &range).field("step", &step).field("value", &self.value());
@@ -387,10 +513,22 @@ impl ToggleWidget {
   }
 
   /// Set the toggled state of the widget.
+  ///
+  /// Doesn't check if the widget is [read-only](WidgetBase::readonly),
+  /// see [`ToggleWidget::try_set_toggled`].
   pub fn set_toggled(&self, value: bool) {
     unsafe { self.set_raw_value::<c_int>(&value.into()) }
   }
 
+  /// Set the toggled state of the widget.
+  ///
+  /// Returns an error if the widget is [read-only](WidgetBase::readonly).
+  pub fn try_set_toggled(&self, value: bool) -> Result<()> {
+    self.check_writable()?;
+    self.set_toggled(value);
+    Ok(())
+  }
+
   fn fmt_fields(&self, f: &mut fmt::DebugStruct) {
     f.field("toggled", &self.toggled());
   }
@@ -436,13 +574,34 @@ impl RadioWidget {
   }
 
   /// Set the current choice.
+  ///
+  /// Returns an error if the widget is [read-only](WidgetBase::readonly).
   pub fn set_choice(&self, value: &str) -> Result<()> {
+    self.check_writable()?;
     unsafe {
       self.set_raw_value::<c_char>(to_c_string!(value));
     }
     Ok(())
   }
 
+  /// Get the index of the current choice, `None` if the value is not one of the choices.
+  pub fn choice_index(&self) -> Option<usize> {
+    let choice = self.choice();
+    self.choices_iter().position(|c| c == choice)
+  }
+
+  /// Set the current choice by its index in [`choices_iter`](Self::choices_iter).
+  ///
+  /// Returns an error if the widget is [read-only](WidgetBase::readonly).
+  pub fn set_choice_index(&self, index: usize) -> Result<()> {
+    self.check_writable()?;
+    try_gp_internal!(gp_widget_get_choice(self.as_ptr(), index.try_into()?, &out choice)?);
+    unsafe {
+      self.set_raw_value::<c_char>(choice);
+    }
+    Ok(())
+  }
+
   fn fmt_fields(&self, f: &mut fmt::DebugStruct) {
     f.field("choices", &MaybeListFmt(|| self.choices_iter())).field("choice", &self.choice());
   }
@@ -459,6 +618,31 @@ impl DateWidget {
     unsafe { self.set_raw_value::<c_int>(&value) }
   }
 
+  /// Get the widget's value as [`SystemTime`].
+  pub fn datetime(&self) -> SystemTime {
+    let timestamp = self.timestamp();
+    let offset = Duration::from_secs(timestamp.unsigned_abs().into());
+
+    if timestamp < 0 {
+      UNIX_EPOCH - offset
+    } else {
+      UNIX_EPOCH + offset
+    }
+  }
+
+  /// Set the widget's value as [`SystemTime`].
+  ///
+  /// Returns an error if the time doesn't fit into a 32-bit UNIX timestamp.
+  pub fn set_datetime(&self, value: SystemTime) -> Result<()> {
+    let timestamp = match value.duration_since(UNIX_EPOCH) {
+      Ok(offset) => c_int::try_from(offset.as_secs())?,
+      Err(error) => -c_int::try_from(error.duration().as_secs())?,
+    };
+
+    self.set_timestamp(timestamp);
+    Ok(())
+  }
+
   fn fmt_fields(&self, f: &mut fmt::DebugStruct) {
     f.field("timestamp", &self.timestamp());
   }
@@ -476,7 +660,175 @@ impl ButtonWidget {
   fn fmt_fields(&self, _f: &mut fmt::DebugStruct) {}
 }
 
+/// Value of a widget
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WidgetValue {
+  /// Value of a [`TextWidget`]
+  Text(String),
+  /// Current choice of a [`RadioWidget`]
+  Choice(String),
+  /// State of a [`ToggleWidget`]
+  Toggle(bool),
+  /// Value of a [`RangeWidget`]
+  Range(f32),
+  /// Value of a [`DateWidget`]
+  Date(SystemTime),
+  /// A [`ButtonWidget`], which has no value
+  Button,
+}
+
+macro_rules! widget_value_conversions {
+  ($($ty:ty => $($variant:ident)|+;)*) => {
+    $(
+      impl TryFrom<WidgetValue> for $ty {
+        type Error = Error;
+
+        fn try_from(value: WidgetValue) -> Result<Self> {
+          match value {
+            $(WidgetValue::$variant(value) => Ok(value),)+
+            _ => Err(Error::bad_parameters(format!(
+              "Expected {} but got {:?}",
+              stringify!($ty),
+              value
+            ))),
+          }
+        }
+      }
+    )*
+  };
+}
+
+widget_value_conversions!(
+  String => Text | Choice;
+  bool => Toggle;
+  f32 => Range;
+  SystemTime => Date;
+);
+
+impl From<bool> for WidgetValue {
+  fn from(value: bool) -> Self {
+    Self::Toggle(value)
+  }
+}
+
+impl From<f32> for WidgetValue {
+  fn from(value: f32) -> Self {
+    Self::Range(value)
+  }
+}
+
+impl From<SystemTime> for WidgetValue {
+  fn from(value: SystemTime) -> Self {
+    Self::Date(value)
+  }
+}
+
 impl Widget {
+  /// Get the value of the widget.
+  ///
+  /// Returns an error for [`GroupWidget`]s and toggles in an unknown state.
+  pub fn value(&self) -> Result<WidgetValue> {
+    Ok(match self {
+      Widget::Group(_) => {
+        return Err(Error::not_supported(format!("Group {} has no value", self.name())))
+      }
+      Widget::Text(text) => WidgetValue::Text(text.value()),
+      Widget::Radio(radio) => WidgetValue::Choice(radio.choice()),
+      Widget::Toggle(toggle) => WidgetValue::Toggle(toggle.toggled().ok_or_else(|| {
+        Error::not_supported(format!("Toggle {} has an unknown state", self.name()))
+      })?),
+      Widget::Range(range) => WidgetValue::Range(range.value()),
+      Widget::Date(date) => WidgetValue::Date(date.datetime()),
+      Widget::Button(_) => WidgetValue::Button,
+    })
+  }
+
+  /// Set the value of the widget.
+  ///
+  /// The value must match the type of the widget, [`WidgetValue::Text`] and
+  /// [`WidgetValue::Choice`] are interchangeable.
+  pub fn set_value(&self, value: WidgetValue) -> Result<()> {
+    self.check_writable()?;
+
+    match (self, value) {
+      (Widget::Text(text), WidgetValue::Text(value) | WidgetValue::Choice(value)) => {
+        text.set_value(&value)
+      }
+      (Widget::Radio(radio), WidgetValue::Text(value) | WidgetValue::Choice(value)) => {
+        radio.set_choice(&value)
+      }
+      (Widget::Toggle(toggle), WidgetValue::Toggle(value)) => toggle.try_set_toggled(value),
+      (Widget::Range(range), WidgetValue::Range(value)) => range.try_set_value(value),
+      (Widget::Date(date), WidgetValue::Date(value)) => date.set_datetime(value),
+      (widget, value) => Err(Error::bad_parameters(format!("Cannot set {value:?} on {widget:?}"))),
+    }
+  }
+
+  /// Set the value of the widget from a string, parsed according to the widget type.
+  ///
+  ///  - Toggles accept `on` / `off`, `true` / `false`, `yes` / `no` and `1` / `0`
+  ///  - Ranges accept floats within the range of the widget
+  ///  - Radios accept one of the choices, compared case-insensitively if there is no exact match
+  ///  - Dates accept a UNIX timestamp or `now`
+  ///  - Texts accept any value
+  ///
+  /// Returns a [`BadParameters`](crate::error::ErrorKind::BadParameters) error if the
+  /// value cannot be parsed.
+  pub fn set_from_str(&self, value: &str) -> Result<()> {
+    let bad_value = |expected: &str| {
+      Error::bad_parameters(format!(
+        "Invalid value {value:?} for {}, expected {expected}",
+        self.name()
+      ))
+    };
+
+    let value = match self {
+      Widget::Toggle(_) => WidgetValue::Toggle(match value.trim().to_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => true,
+        "off" | "false" | "no" | "0" => false,
+        _ => return Err(bad_value("on or off")),
+      }),
+      Widget::Range(range) => {
+        let (range, _) = range.range_and_step();
+        let number = value.trim().parse::<f32>().map_err(|_| bad_value("a number"))?;
+
+        if !range.contains(&number) {
+          return Err(bad_value(&format!("a number in {range:?}")));
+        }
+
+        WidgetValue::Range(number)
+      }
+      Widget::Radio(radio) => {
+        let choices: Vec<String> = radio.choices_iter().collect();
+
+        let choice = choices
+          .iter()
+          .find(|choice| *choice == value)
+          .or_else(|| choices.iter().find(|choice| choice.eq_ignore_ascii_case(value.trim())))
+          .ok_or_else(|| bad_value(&format!("one of {choices:?}")))?;
+
+        WidgetValue::Choice(choice.clone())
+      }
+      Widget::Date(_) => WidgetValue::Date(match value.trim() {
+        "now" => SystemTime::now(),
+        timestamp => {
+          let timestamp = timestamp.parse::<i64>().map_err(|_| bad_value("a UNIX timestamp"))?;
+          let offset = Duration::from_secs(timestamp.unsigned_abs());
+
+          if timestamp < 0 {
+            UNIX_EPOCH - offset
+          } else {
+            UNIX_EPOCH + offset
+          }
+        }
+      }),
+      _ => WidgetValue::Text(value.to_owned()),
+    };
+
+    self.set_value(value)
+  }
+
   pub(crate) fn new_shared(widget: BackgroundPtr<libgphoto2_sys::CameraWidget>) -> Self {
     try_gp_internal!(gp_widget_ref(*widget).unwrap());
     Self::new_owned(widget)
@@ -0,0 +1,147 @@
+//! Background threads forwarding camera events
+//!
+//! Shared by [`Camera::spawn_event_pump`](crate::Camera::spawn_event_pump) and the workers of
+//! [`CameraPool`](crate::pool::CameraPool) and [`EventMux`](crate::pool::EventMux).
+
+use crate::{camera::CameraEvent, Camera, Result};
+use crossbeam_channel::{Receiver, RecvTimeoutError, TryRecvError};
+use std::{
+  sync::{Condvar, Mutex, MutexGuard, PoisonError},
+  time::Duration,
+};
+
+#[derive(Default)]
+struct PauseState {
+  paused: bool,
+  polling: usize,
+}
+
+/// Lets event workers be paused between two polls
+#[derive(Default)]
+pub(crate) struct PauseGate {
+  state: Mutex<PauseState>,
+  changed: Condvar,
+}
+
+impl PauseGate {
+  fn lock(&self) -> MutexGuard<'_, PauseState> {
+    self.state.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  fn wait<'a>(&self, state: MutexGuard<'a, PauseState>) -> MutexGuard<'a, PauseState> {
+    self.changed.wait(state).unwrap_or_else(PoisonError::into_inner)
+  }
+
+  /// Pause the workers, waits until none of them is polling anymore
+  ///
+  /// The workers resume when the returned guard is dropped.
+  pub(crate) fn pause(&self) -> Paused<'_> {
+    let mut state = self.lock();
+    state.paused = true;
+
+    while state.polling > 0 {
+      state = self.wait(state);
+    }
+
+    Paused(self)
+  }
+
+  /// Run a poll of a worker, waits while the workers are paused
+  fn poll<T>(&self, poll: impl FnOnce() -> T) -> T {
+    let mut state = self.lock();
+    while state.paused {
+      state = self.wait(state);
+    }
+    state.polling += 1;
+    drop(state);
+
+    let result = poll();
+
+    self.lock().polling -= 1;
+    self.changed.notify_all();
+
+    result
+  }
+}
+
+/// Workers of a [`PauseGate`] are paused while this exists
+pub(crate) struct Paused<'a>(&'a PauseGate);
+
+impl Drop for Paused<'_> {
+  fn drop(&mut self) {
+    self.0.lock().paused = false;
+    self.0.changed.notify_all();
+  }
+}
+
+/// Wait for events of `camera` and pass them to `forward` until `forward` returns false or
+/// `stop` is disconnected, [`CameraEvent::Timeout`] events are skipped
+///
+/// If a `pause` gate is given, no events are polled while it is paused.
+pub(crate) fn event_worker(
+  camera: Camera,
+  poll_timeout: Duration,
+  stop: Receiver<()>,
+  pause: Option<&PauseGate>,
+  mut forward: impl FnMut(Result<CameraEvent>) -> bool,
+) {
+  let poll = || camera.wait_event(poll_timeout).wait();
+
+  // Dropping the stop sender disconnects the channel and stops the worker
+  while let Err(TryRecvError::Empty) = stop.try_recv() {
+    let event = match pause.map_or_else(poll, |pause| pause.poll(poll)) {
+      Ok(CameraEvent::Timeout) => continue,
+      event => event,
+    };
+    let is_error = event.is_err();
+
+    if !forward(event) {
+      break;
+    }
+
+    // Don't flood the stream with errors if the camera is gone
+    if is_error && !matches!(stop.recv_timeout(poll_timeout), Err(RecvTimeoutError::Timeout)) {
+      break;
+    }
+  }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::PauseGate;
+  use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+  };
+
+  #[test]
+  fn test_pause_waits_for_polls() {
+    let gate = PauseGate::default();
+    let polling = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+      scope.spawn(|| {
+        gate.poll(|| {
+          polling.store(true, Ordering::SeqCst);
+          thread::sleep(Duration::from_millis(100));
+          polling.store(false, Ordering::SeqCst);
+        })
+      });
+
+      while !polling.load(Ordering::SeqCst) {
+        thread::yield_now();
+      }
+
+      let paused = gate.pause();
+      assert!(!polling.load(Ordering::SeqCst));
+
+      let resumed = scope.spawn(|| gate.poll(|| polling.load(Ordering::SeqCst)));
+      thread::sleep(Duration::from_millis(50));
+      assert!(!resumed.is_finished());
+
+      drop(paused);
+      assert!(!resumed.join().unwrap());
+    });
+  }
+}
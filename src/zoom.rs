@@ -0,0 +1,145 @@
+//! Power zoom control
+//!
+//! Controls the power zoom of the lens through the `zoom` configuration,
+//! for cameras and lenses which support it.
+//!
+//! ## Zooming in
+//! ```no_run
+//! use gphoto2::{zoom::ZoomCommand, Context, Result};
+//! use std::{thread::sleep, time::Duration};
+//!
+//! # fn main() -> Result<()> {
+//! let camera = Context::new()?.autodetect_camera().wait()?;
+//!
+//! camera.zoom(ZoomCommand::In)?;
+//! sleep(Duration::from_millis(500));
+//! camera.zoom(ZoomCommand::Stop)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{widget::Widget, Camera, Error, Result};
+
+/// Command for the power zoom
+///
+/// Drivers expose the zoom in different ways, this enum maps onto them:
+///  - Choices (e.g. `Tele` / `Wide` / `Stop`) select the matching choice
+///  - Ranges around 0 (e.g. `-1..=1`) are treated as direction and speed, `Stop` sets 0
+///  - Other ranges are treated as zoom position, `In` / `Out` move by one increment
+///    and `Stop` does nothing
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ZoomCommand {
+  /// Zoom in (towards tele)
+  In,
+  /// Zoom out (towards wide)
+  Out,
+  /// Stop zooming
+  Stop,
+}
+
+impl ZoomCommand {
+  /// Words used by drivers for the command in choices
+  fn choice_names(self) -> &'static [&'static str] {
+    match self {
+      Self::In => &["in", "tele"],
+      Self::Out => &["out", "wide"],
+      Self::Stop => &["stop"],
+    }
+  }
+
+  /// Choice of a radio configuration which has a word matching the command
+  fn find_choice(self, choices: impl IntoIterator<Item = String>) -> Option<String> {
+    choices.into_iter().find(|choice| {
+      let choice = choice.to_lowercase();
+      self.choice_names().iter().any(|name| choice.split_whitespace().any(|word| word == *name))
+    })
+  }
+
+  /// Value of a range configuration from `min` to `max` currently at `current`,
+  /// `None` if the value should not change
+  fn range_value(self, min: f32, max: f32, step: f32, current: f32) -> Option<f32> {
+    if min < 0.0 && max > 0.0 {
+      return Some(match self {
+        Self::In => max,
+        Self::Out => min,
+        Self::Stop => 0.0,
+      });
+    }
+
+    let step = if step > 0.0 { step } else { 1.0 };
+
+    match self {
+      Self::In => Some((current + step).min(max)),
+      Self::Out => Some((current - step).max(min)),
+      Self::Stop => None,
+    }
+  }
+}
+
+/// Apply a [`ZoomCommand`] to the `zoom` configuration of the camera
+pub(crate) fn zoom(camera: &Camera, command: ZoomCommand) -> Result<()> {
+  let widget = camera.config_key::<Widget>("zoom").wait()?;
+
+  match &widget {
+    Widget::Range(range_widget) => {
+      let (range, step) = range_widget.range_and_step();
+      let value = command.range_value(*range.start(), *range.end(), step, range_widget.value());
+
+      match value {
+        Some(value) => range_widget.try_set_value(value)?,
+        None => return Ok(()),
+      }
+    }
+    Widget::Radio(radio) => {
+      let choice = command
+        .find_choice(radio.choices_iter())
+        .ok_or_else(|| Error::not_supported(format!("zoom has no choice for {command:?}")))?;
+
+      radio.set_choice(&choice)?;
+    }
+    _ => return Err(Error::not_supported(format!("Unsupported zoom widget: {widget:?}"))),
+  }
+
+  camera.set_config(&widget).wait()
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+  use super::ZoomCommand;
+
+  fn choices(choices: &[&str]) -> Vec<String> {
+    choices.iter().map(|choice| choice.to_string()).collect()
+  }
+
+  #[test]
+  fn test_find_choice() {
+    let tele_wide = choices(&["Tele", "Wide", "Stop"]);
+    assert_eq!(ZoomCommand::In.find_choice(tele_wide.clone()).as_deref(), Some("Tele"));
+    assert_eq!(ZoomCommand::Out.find_choice(tele_wide.clone()).as_deref(), Some("Wide"));
+    assert_eq!(ZoomCommand::Stop.find_choice(tele_wide).as_deref(), Some("Stop"));
+
+    let in_out = choices(&["Zoom In", "Zoom Out"]);
+    assert_eq!(ZoomCommand::Out.find_choice(in_out.clone()).as_deref(), Some("Zoom Out"));
+    assert_eq!(ZoomCommand::Stop.find_choice(in_out), None);
+
+    // Only whole words match
+    assert_eq!(ZoomCommand::In.find_choice(choices(&["Telephoto", "Inside"])), None);
+  }
+
+  #[test]
+  fn test_range_value_speed() {
+    assert_eq!(ZoomCommand::In.range_value(-1.0, 1.0, 1.0, 0.0), Some(1.0));
+    assert_eq!(ZoomCommand::Out.range_value(-1.0, 1.0, 1.0, 0.0), Some(-1.0));
+    assert_eq!(ZoomCommand::Stop.range_value(-1.0, 1.0, 1.0, 1.0), Some(0.0));
+  }
+
+  #[test]
+  fn test_range_value_position() {
+    assert_eq!(ZoomCommand::In.range_value(0.0, 100.0, 5.0, 50.0), Some(55.0));
+    assert_eq!(ZoomCommand::Out.range_value(0.0, 100.0, 0.0, 50.0), Some(49.0));
+    assert_eq!(ZoomCommand::In.range_value(0.0, 100.0, 5.0, 98.0), Some(100.0));
+    assert_eq!(ZoomCommand::Out.range_value(0.0, 100.0, 5.0, 2.0), Some(0.0));
+    assert_eq!(ZoomCommand::Stop.range_value(0.0, 100.0, 5.0, 50.0), None);
+  }
+}